@@ -0,0 +1,77 @@
+//! Benchmarks parsing and both parts of every registered day.
+//!
+//! Each day is benchmarked against its real input in `inputs/dayNN.txt`
+//! (or the directory named by `AOC_INPUTS`). Days without an input fall back
+//! to their first example in `tests/examples`, so that every day is still
+//! covered. Parts that are not solved, or fail on the input, are left out.
+//!
+//! To compare against an earlier run:
+//!
+//! ```bash
+//! $ cargo bench -- --save-baseline before
+//! $ cargo bench -- --baseline before
+//! ```
+
+use std::env;
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use criterion::{ criterion_group, criterion_main, Criterion };
+
+use advent_of_code_2023::runner;
+use advent_of_code_2023::solver::{ DynSolver, SOLVERS };
+
+fn find_input(day: u32) -> Option<PathBuf> {
+    let inputs = env::var("AOC_INPUTS").unwrap_or_else(|_| "inputs".to_string());
+    let input = runner::input_path(Path::new(&inputs), day);
+    if input.exists() {
+        return Some(input);
+    }
+
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/examples");
+    let prefix = format!("day{:02}", day);
+    let mut candidates: Vec<PathBuf> = fs::read_dir(examples).ok()?
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .filter(|path| path.file_name().unwrap().to_string_lossy().starts_with(&prefix))
+        .collect();
+    candidates.sort();
+    candidates.into_iter().next()
+}
+
+fn bench_day(c: &mut Criterion, solver: &dyn DynSolver) {
+    let day = solver.day();
+    let Some(path) = find_input(day) else {
+        eprintln!("day {}: no input or example found, skipping", day);
+        return;
+    };
+    let input = fs::read_to_string(&path).unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+
+    let parsed = match solver.parse(&input) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("day {}: {}: {}, skipping", day, path.display(), err);
+            return;
+        },
+    };
+
+    let mut group = c.benchmark_group(format!("day{:02}", day));
+    group.bench_function("parse", |b| b.iter(|| solver.parse(&input)));
+
+    if solver.part1(&parsed).is_ok() {
+        group.bench_function("part1", |b| b.iter(|| solver.part1(&parsed)));
+    }
+    if solver.part2(&parsed).is_ok() {
+        group.bench_function("part2", |b| b.iter(|| solver.part2(&parsed)));
+    }
+    group.finish();
+}
+
+fn bench_solvers(c: &mut Criterion) {
+    for solver in SOLVERS {
+        bench_day(c, *solver);
+    }
+}
+
+criterion_group!(benches, bench_solvers);
+criterion_main!(benches);
@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use toml::Spanned;
+
+use crate::error::{ Error, Result };
+use crate::runner::{ Status, Summary };
+
+/// Answers accepted by the Advent of Code website, recorded so that a change
+/// to a solver can be checked against them. They are kept in a TOML file
+/// with one table per day:
+///
+/// ```toml
+/// [day01]
+/// part1 = 54390
+/// part2 = "54277"
+/// ```
+#[derive(Debug, Default)]
+pub struct Answers {
+    answers: BTreeMap<(u32, u32), String>,
+}
+
+impl Answers {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|source| Error::Io { path: path.display().to_string(), source })?;
+        Self::parse(&text)
+    }
+
+    /// Parse the answers file. Errors point at the offending line and column
+    /// of `text`, so they should be shown against it rather than against a
+    /// puzzle input.
+    pub fn parse(text: &str) -> Result<Self> {
+        let error_at = |span: Range<usize>, message: &str| {
+            let (line, column) = line_and_column(text, span.start);
+            Error::parse(line, column, message)
+        };
+
+        // the spans let errors point at the key they are about
+        let table: BTreeMap<Spanned<String>, BTreeMap<Spanned<String>, Spanned<toml::Value>>> = toml::from_str(text)
+            .map_err(|err| error_at(err.span().unwrap_or(0..0), err.message()))?;
+
+        let mut answers = BTreeMap::new();
+
+        for (day_key, parts) in &table {
+            let day = day_key.get_ref().strip_prefix("day")
+                .and_then(|d| d.parse::<u32>().ok())
+                .ok_or_else(|| error_at(day_key.span(), &format!("{}: expected a table named like [day01]", day_key.get_ref())))?;
+
+            for (part_key, answer) in parts {
+                let part = match part_key.get_ref().as_str() {
+                    "part1" => 1,
+                    "part2" => 2,
+                    key => return Err(error_at(part_key.span(), &format!("{}: expected part1 or part2", key))),
+                };
+                let answer = match answer.get_ref() {
+                    toml::Value::String(s) => s.clone(),
+                    toml::Value::Integer(i) => i.to_string(),
+                    _ => return Err(error_at(answer.span(), "expected the answer as a string or an integer")),
+                };
+                answers.insert((day, part), answer);
+            }
+        }
+
+        Ok(Answers { answers })
+    }
+
+    pub fn get(&self, day: u32, part: u32) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+
+    /// All recorded answers as `(day, part, answer)`, ordered by day and part.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, u32, &str)> {
+        self.answers.iter().map(|(&(day, part), answer)| (day, part, answer.as_str()))
+    }
+}
+
+fn line_and_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+/// Result of checking one part against the recorded answers.
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String, actual: String },
+    /// There is no recorded answer for this part.
+    Missing,
+    /// The part was not run, so there is nothing to check.
+    Skipped(String),
+    /// The part has a recorded answer but was not run, so the answer could
+    /// not be checked.
+    Unchecked(String),
+    /// The solver returned an error instead of an answer.
+    Error(String),
+}
+
+impl Verdict {
+    /// Whether this verdict means a solver no longer produces the answer it
+    /// used to.
+    pub fn is_regression(&self) -> bool {
+        matches!(self, Verdict::Fail { .. } | Verdict::Error(_))
+    }
+}
+
+pub struct Verification {
+    pub rows: Vec<(u32, u32, Verdict)>,
+}
+
+impl Verification {
+    pub fn regressions(&self) -> usize {
+        self.rows.iter().filter(|(_, _, v)| v.is_regression()).count()
+    }
+
+    pub fn unchecked(&self) -> usize {
+        self.rows.iter().filter(|(_, _, v)| matches!(v, Verdict::Unchecked(_))).count()
+    }
+}
+
+/// Compare the answers from a run against the recorded ones.
+pub fn verify(summary: &Summary, answers: &Answers) -> Verification {
+    let rows = summary.days.iter().flat_map(|day| {
+        day.parts.iter().map(|p| {
+            let verdict = match (&p.status, answers.get(day.day, p.part)) {
+                (Status::Skipped(reason), None) => Verdict::Skipped(reason.clone()),
+                (Status::Skipped(reason), Some(_)) => Verdict::Unchecked(reason.clone()),
+                (Status::Failed(reason), _) => Verdict::Error(reason.clone()),
+                (Status::Solved(_), None) => Verdict::Missing,
+                (Status::Solved(actual), Some(expected)) if actual == expected => Verdict::Pass,
+                (Status::Solved(actual), Some(expected)) => Verdict::Fail {
+                    expected: expected.to_string(),
+                    actual: actual.clone(),
+                },
+            };
+            (day.day, p.part, verdict)
+        })
+    }).collect();
+
+    Verification { rows }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail { expected, actual } => write!(f, "FAIL: expected {}, got {}", expected, actual),
+            Verdict::Missing => write!(f, "missing (no recorded answer)"),
+            Verdict::Skipped(reason) => write!(f, "skipped ({})", reason),
+            Verdict::Unchecked(reason) => write!(f, "UNCHECKED: {}", reason),
+            Verdict::Error(reason) => write!(f, "FAIL: {}", reason),
+        }
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>3}  {:>4}  Result", "Day", "Part")?;
+        for (day, part, verdict) in &self.rows {
+            writeln!(f, "{:>3}  {:>4}  {}", day, part, verdict)?;
+        }
+
+        let count = |pred: fn(&Verdict) -> bool| self.rows.iter().filter(|(_, _, v)| pred(v)).count();
+        write!(f, "{} passed, {} failed, {} unchecked, {} missing, {} skipped",
+            count(|v| *v == Verdict::Pass),
+            self.regressions(),
+            self.unchecked(),
+            count(|v| *v == Verdict::Missing),
+            count(|v| matches!(v, Verdict::Skipped(_))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    
+    use super::*;
+    use crate::runner::{ DayRun, PartRun, Timing };
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse("[day01]\npart1 = 142\npart2 = \"281\"\n\n[day6]\npart1 = 288\n").unwrap();
+        assert_eq!(Some("142"), answers.get(1, 1));
+        assert_eq!(Some("281"), answers.get(1, 2));
+        assert_eq!(Some("288"), answers.get(6, 1));
+        assert_eq!(None, answers.get(6, 2));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Answers::parse("[day01]\npart3 = 142\n").unwrap_err();
+        assert_eq!("line 2, column 1: part3: expected part1 or part2", err.to_string());
+
+        let err = Answers::parse("[first]\npart1 = 142\n").unwrap_err();
+        assert_eq!("line 1, column 2: first: expected a table named like [day01]", err.to_string());
+
+        let err = Answers::parse("[day01]\npart1 = \n").unwrap_err();
+        assert!(err.to_string().starts_with("line 2, column 9:"), "{}", err);
+
+        // the error points at the part2 under day02, not the first part2 in the file
+        let err = Answers::parse("[day01]\npart2 = 1\n[day02]\npart2 = true\n").unwrap_err();
+        assert_eq!("line 4, column 9: expected the answer as a string or an integer", err.to_string());
+
+        let err = Answers::parse("[day01]\npart1 = 1\n[day02]\npart1 = 2\n[day03]\npart1x = 3\n").unwrap_err();
+        assert_eq!("line 6, column 1: part1x: expected part1 or part2", err.to_string());
+
+        let err = Answers::parse("day01 = 5\n").unwrap_err();
+        assert!(err.to_string().starts_with("line 1, column 9:"), "{}", err);
+    }
+
+    #[test]
+    fn test_verify() {
+        let part = |part, status| PartRun { part, status, time: Timing::default() };
+        let summary = Summary { days: vec!(
+            DayRun {
+                day: 1, title: "", input: PathBuf::new(), parse_time: Timing::default(),
+                parts: vec!(
+                    part(1, Status::Solved("142".to_string())),
+                    part(2, Status::Solved("280".to_string())),
+                ),
+            },
+            DayRun {
+                day: 14, title: "", input: PathBuf::new(), parse_time: Timing::default(),
+                parts: vec!(
+                    part(1, Status::Solved("136".to_string())),
+                    part(2, Status::Skipped("not solved yet".to_string())),
+                ),
+            },
+            DayRun {
+                day: 6, title: "", input: PathBuf::new(), parse_time: Timing::default(),
+                parts: vec!(
+                    part(1, Status::Skipped("no input file".to_string())),
+                ),
+            },
+        )};
+        let answers = Answers::parse("[day01]\npart1 = 142\npart2 = 281\n[day06]\npart1 = 288\n").unwrap();
+
+        let verification = verify(&summary, &answers);
+        let verdicts: Vec<&Verdict> = verification.rows.iter().map(|(_, _, v)| v).collect();
+        assert_eq!(vec!(
+            &Verdict::Pass,
+            &Verdict::Fail { expected: "281".to_string(), actual: "280".to_string() },
+            &Verdict::Missing,
+            &Verdict::Skipped("not solved yet".to_string()),
+            &Verdict::Unchecked("no input file".to_string()),
+        ), verdicts);
+        assert_eq!(1, verification.regressions());
+        assert_eq!(1, verification.unchecked());
+        assert!(verification.to_string().ends_with("1 passed, 1 failed, 1 unchecked, 1 missing, 1 skipped"));
+    }
+}
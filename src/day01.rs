@@ -1,61 +1,147 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>) -> String {
+use crate::error::{ Error, Result };
+use crate::solver::Solver;
 
-    let mut total = 0;
+pub struct Day01;
 
-    for line in input {
-        let line = line.unwrap();
-        let calibration = 10*get_first_digit(&line) + get_last_digit(&line);
-        total += calibration;
+impl Solver for Day01 {
+    type Input = Vec<String>;
+
+    fn day(&self) -> u32 {
+        1
     }
 
-    total.to_string()
-}
+    fn title(&self) -> &'static str {
+        "Trebuchet?!"
+    }
 
-fn get_first_digit(line: &String) -> u32 {
-    for c in line.chars() {
-        match c {
-            '0'..='9' => return c.to_digit(10).unwrap(),
-            '\0'.. => {},
-        }
+    fn parse(&self, input: &str) -> Result<Vec<String>> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part1(&self, lines: &Vec<String>) -> Result<String> {
+        solve_part1(lines)
     }
-    panic!("Line does not contain any digits! {}", line);
+
+    fn part2(&self, lines: &Vec<String>) -> Result<String> {
+        solve_part2(lines)
+    }
+}
+
+/// The words for one to nine in English, as used in part 2.
+pub const ENGLISH: &[(&str, u32)] = &[
+    ("one", 1), ("two", 2), ("three", 3), ("four", 4), ("five", 5),
+    ("six", 6), ("seven", 7), ("eight", 8), ("nine", 9),
+];
+
+/// The tokens that count as a digit in a calibration line, each with its
+/// value. Tokens may overlap, as in "twone", and each of them counts.
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    tokens: Vec<(String, u32)>,
 }
 
-fn get_last_digit(line: &String) -> u32 {
-    for c in line.chars().rev() {
-        match c {
-            '0'..='9' => return c.to_digit(10).unwrap(),
-            '\0'.. => {},
+impl Vocabulary {
+    /// The numerals 0 to 9, which are all that part 1 recognises.
+    pub fn numerals() -> Self {
+        Vocabulary::default().with_words(&[
+            ("0", 0), ("1", 1), ("2", 2), ("3", 3), ("4", 4),
+            ("5", 5), ("6", 6), ("7", 7), ("8", 8), ("9", 9),
+        ])
+    }
+
+    /// Also recognise `words`, such as `ENGLISH`, each with its value. Empty
+    /// words are ignored.
+    pub fn with_words(mut self, words: &[(&str, u32)]) -> Self {
+        self.tokens.extend(words.iter()
+            .filter(|(word, _)| !word.is_empty())
+            .map(|(word, value)| (word.to_string(), *value)));
+        self
+    }
+
+    fn token_at(&self, rest: &str) -> Option<u32> {
+        self.tokens.iter()
+            .find(|(token, _)| rest.starts_with(token.as_str()))
+            .map(|(_, value)| *value)
+    }
+
+    /// The values of the first and last tokens in `line`, found in a single
+    /// pass over it, or None if it has no tokens at all.
+    pub fn first_and_last(&self, line: &str) -> Option<(u32, u32)> {
+        let mut found = None;
+        for (i, _) in line.char_indices() {
+            if let Some(value) = self.token_at(&line[i..]) {
+                found = match found {
+                    None => Some((value, value)),
+                    Some((first, _)) => Some((first, value)),
+                };
+            }
         }
+        found
     }
-    panic!("Line does not contain any digits! {}", line);
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
+pub(crate) fn solve_part1(lines: &[String]) -> Result<String> {
+    sum_calibration_values(lines, &Vocabulary::numerals())
+}
+
+pub(crate) fn solve_part2(lines: &[String])  -> Result<String> {
+    sum_calibration_values(lines, &Vocabulary::numerals().with_words(ENGLISH))
+}
+
+fn sum_calibration_values(lines: &[String], vocabulary: &Vocabulary) -> Result<String> {
 
     let mut total = 0;
 
-    for line in input {
-        let line = replace_text_digits(line.unwrap());
-        let calibration = 10*get_first_digit(&line) + get_last_digit(&line);
+    for (i, line) in lines.iter().enumerate() {
+        let calibration = calibration_value(line, i + 1, vocabulary)?;
         total += calibration;
     }
 
-    total.to_string()
+    Ok(total.to_string())
+}
+
+fn calibration_value(line: &str, line_no: usize, vocabulary: &Vocabulary) -> Result<u32> {
+    match vocabulary.first_and_last(line) {
+        Some((first, last)) => Ok(10*first + last),
+        None => Err(Error::parse(line_no, 1, "line does not contain any digits")),
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_and_last() {
+        let numerals = Vocabulary::numerals();
+        assert_eq!(Some((1, 2)), numerals.first_and_last("1abc2"));
+        assert_eq!(Some((7, 7)), numerals.first_and_last("treb7uchet"));
+        assert_eq!(None, numerals.first_and_last("eightwothree"));
+
+        let english = Vocabulary::numerals().with_words(ENGLISH);
+        assert_eq!(Some((8, 3)), english.first_and_last("eightwothree"));
+        assert_eq!(Some((2, 1)), english.first_and_last("xtwone"));
+        assert_eq!(Some((1, 8)), english.first_and_last("zoneight"));
+        assert_eq!(Some((3, 3)), english.first_and_last("ünthree"));
+        assert_eq!(None, english.first_and_last("zero, nothing, nada"));
+    }
+
+    #[test]
+    fn test_other_languages() {
+        let german = Vocabulary::numerals().with_words(&[("eins", 1), ("zwei", 2), ("drei", 3), ("", 4)]);
+        assert_eq!(Some((2, 1)), german.first_and_last("zweidreieins"));
+        assert_eq!(Some((5, 3)), german.first_and_last("xx5twodrei"));
+        assert_eq!(None, german.first_and_last("vier"));
+    }
+
+    #[test]
+    fn test_lines_without_digits() {
+        let lines = vec!("one2".to_string(), "nothing".to_string(), "three".to_string());
+        let err = solve_part1(&lines).unwrap_err();
+        assert_eq!("line 2, column 1: line does not contain any digits", err.to_string());
 
-fn replace_text_digits(line: String) -> String {
-    line.replace("one", "one1one")
-        .replace("two", "two2two")
-        .replace("three", "three3three")
-        .replace("four", "four4four")
-        .replace("five", "five5five")
-        .replace("six", "six6six")
-        .replace("seven", "seven7seven")
-        .replace("eight", "eight8eight")
-        .replace("nine", "nine9nine")
+        let err = solve_part2(&lines).unwrap_err();
+        assert_eq!("line 2, column 1: line does not contain any digits", err.to_string());
+    }
 }
@@ -1,71 +1,191 @@
-use std::cmp;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
 
-use regex::Regex;
-use lazy_static::lazy_static;
+use crate::error::{ self, Error, Line, Result };
+use crate::runner::Format;
+use crate::solver::{ Settings, Solver };
 
-#[derive(Debug, PartialEq)]
+/// The cubes shown in one draw, by colour.
+#[derive(Debug, PartialEq, Default)]
 struct Draw {
-    red: u32,
-    green: u32,
-    blue: u32,
+    cubes: BTreeMap<String, u32>,
 }
 
 impl Draw {
     fn power(&self) -> u32 {
-        self.red * self.green * self.blue
+        self.cubes.values().product()
     }
 }
 
 #[derive(Debug)]
-struct Game {
+pub struct Game {
     id: u32,
     draws: Vec<Draw>
 }
 
+/// A draw that showed more cubes of a colour than the bag holds.
+#[derive(Debug, PartialEq)]
+pub struct Violation {
+    /// Index of the draw within its game, starting from 0.
+    pub draw: usize,
+    pub colour: String,
+    pub count: u32,
+    pub limit: u32,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "draw {} shows {} {}, but the bag only holds {}", self.draw + 1, self.count, self.colour, self.limit)
+    }
+}
+
 impl Game {
-    fn is_valid(&self, max_red: u32, max_green: u32, max_blue: u32) -> bool {
-        for draw in self.draws.iter() {
-            if draw.red > max_red || draw.green > max_green || draw.blue > max_blue {
-                return false;
+    fn is_valid(&self, limits: &CubeLimits) -> bool {
+        self.violations(limits).is_empty()
+    }
+
+    /// Every draw, and colour within it, that is impossible with `limits`.
+    pub fn violations(&self, limits: &CubeLimits) -> Vec<Violation> {
+        let mut violations = vec!();
+        for (i, draw) in self.draws.iter().enumerate() {
+            for (colour, count) in &draw.cubes {
+                let limit = limits.limit(colour);
+                if *count > limit {
+                    violations.push(Violation { draw: i, colour: colour.clone(), count: *count, limit });
+                }
             }
         }
-        true
+        violations
     }
 
-    fn min_cubes_of_each_color(&self) -> Draw {
-        let mut min_red = 0;
-        let mut min_blue = 0;
-        let mut min_green = 0;
+    /// The fewest cubes of each colour that the bag could have held. Every
+    /// colour in `limits` is included, with 0 for those the game never shows.
+    fn min_cubes_of_each_color(&self, limits: &CubeLimits) -> Draw {
+        let mut min_cubes = Draw::default();
+        for colour in limits.limits.keys() {
+            min_cubes.cubes.insert(colour.clone(), 0);
+        }
 
         for draw in self.draws.iter() {
-            min_red = cmp::max(draw.red, min_red);
-            min_blue = cmp::max(draw.blue, min_blue);
-            min_green = cmp::max(draw.green, min_green);
+            for (colour, count) in &draw.cubes {
+                let min = min_cubes.cubes.entry(colour.clone()).or_insert(0);
+                *min = (*min).max(*count);
+            }
         }
 
-        Draw{ red: min_red, blue: min_blue, green: min_green}
+        min_cubes
     }
 }
 
-lazy_static! {
-    static ref RED_RE: Regex = Regex::new(r"(?<count>\d+) red").unwrap();
-    static ref BLUE_RE: Regex = Regex::new(r"(?<count>\d+) blue").unwrap();
-    static ref GREEN_RE: Regex = Regex::new(r"(?<count>\d+) green").unwrap();
+/// How many cubes of each colour are in the bag. Colours that are not
+/// listed are not in the bag at all.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubeLimits {
+    limits: BTreeMap<String, u32>,
 }
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
+impl CubeLimits {
+    fn limit(&self, colour: &str) -> u32 {
+        self.limits.get(colour).copied().unwrap_or(0)
+    }
+}
+
+impl Default for CubeLimits {
+    /// The bag from the puzzle: 12 red, 13 green and 14 blue cubes.
+    fn default() -> Self {
+        "red=12,green=13,blue=14".parse().unwrap()
+    }
+}
+
+impl FromStr for CubeLimits {
+    type Err = Error;
 
-    let max_red: u32 = 12;
-    let max_green: u32 = 13;
-    let max_blue: u32 = 14;
+    /// Parse limits written as `red=12,green=13,blue=14`.
+    fn from_str(s: &str) -> Result<Self> {
+        let usage = || Error::Usage(format!("expected cube limits such as red=12,green=13,blue=14, found {:?}", s));
+
+        let mut limits = BTreeMap::new();
+        for limit in s.split(',') {
+            let (colour, count) = limit.split_once('=').ok_or_else(usage)?;
+            let count = count.trim().parse::<u32>().map_err(|_| usage())?;
+            if colour.trim().is_empty() {
+                return Err(usage());
+            }
+            limits.insert(colour.trim().to_string(), count);
+        }
+        Ok(CubeLimits { limits })
+    }
+}
+
+/// Solver for day 2. Part 1 checks the games against the bag described by
+/// its `CubeLimits`, which default to the ones from the puzzle.
+#[derive(Default)]
+pub struct Day02 {
+    limits: Option<CubeLimits>,
+}
+
+impl Day02 {
+    pub const fn new() -> Self {
+        Day02 { limits: None }
+    }
+
+    pub fn with_limits(limits: CubeLimits) -> Self {
+        Day02 { limits: Some(limits) }
+    }
+
+    fn with_default_limits<T>(&self, f: impl FnOnce(&CubeLimits) -> T) -> T {
+        match &self.limits {
+            Some(limits) => f(limits),
+            None => f(&CubeLimits::default()),
+        }
+    }
+}
+
+impl Solver for Day02 {
+    type Input = Vec<Game>;
+
+    fn day(&self) -> u32 {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "Cube Conundrum"
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<Game>> {
+        error::lines(input).map(parse_game).collect()
+    }
+
+    fn part1(&self, games: &Vec<Game>) -> Result<String> {
+        Ok(self.with_default_limits(|limits| solve_part1(games, limits)))
+    }
+
+    fn part2(&self, games: &Vec<Game>) -> Result<String> {
+        Ok(self.with_default_limits(|limits| solve_part2(games, limits)))
+    }
+
+    fn explain(&self, games: &Vec<Game>, format: Format) -> Option<String> {
+        match format {
+            Format::Text => Some(self.with_default_limits(|limits| explain_games(games, limits))),
+            _ => None,
+        }
+    }
+
+    /// Picks up the bag from the `cubes` setting, written as for `CubeLimits`.
+    fn configure(&self, settings: &Settings) -> Result<Option<Self>> {
+        settings.get("cubes")
+            .map(|cubes| Ok(Day02::with_limits(cubes.parse()?)))
+            .transpose()
+    }
+}
+
+pub(crate) fn solve_part1(games: &[Game], limits: &CubeLimits)  -> String {
 
     let mut total = 0;
 
-    for line in input {
-        let g = parse_game(line.unwrap());
-        if g.is_valid(max_red, max_green, max_blue) {
+    for g in games {
+        if g.is_valid(limits) {
             total += g.id
         }
     }
@@ -73,47 +193,54 @@ pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
     total.to_string()
 }
 
-fn parse_game(line: String) -> Game {
-    let id_start = line.find(" ").unwrap() + 1;
-    let colon = line.find(":").unwrap();
-    let id = (&line[id_start..colon]).parse::<u32>().unwrap();
-
-    let draws = line.split("; ");
-    let draws: Vec<Draw> = draws.map(|d| parse_draw(d)).collect();
-
-    Game{id, draws}
+/// List each game as possible or not, with the draws that rule it out.
+fn explain_games(games: &[Game], limits: &CubeLimits) -> String {
+    let mut explanation = String::new();
+    for g in games {
+        let violations = g.violations(limits);
+        if violations.is_empty() {
+            explanation += &format!("Game {}: possible\n", g.id);
+        } else {
+            explanation += &format!("Game {}: impossible\n", g.id);
+            for violation in violations {
+                explanation += &format!("  {}\n", violation);
+            }
+        }
+    }
+    explanation
 }
 
-fn parse_draw(text: &str) -> Draw {
-    let red = match RED_RE.captures(text) {
-        Some(caps) => (&caps["count"]).parse::<u32>().unwrap(),
-        None => 0,
-    };
+fn parse_game(line: Line) -> Result<Game> {
+    let colon = line.text.find(":")
+        .ok_or_else(|| line.error(line.text, "expected \"Game <id>: <draws>\""))?;
+    let id_start = line.text[..colon].find(" ").map_or(0, |i| i + 1);
+    let id = line.parse::<u32>(&line.text[id_start..colon])?;
 
-    let green = match GREEN_RE.captures(text) {
-        Some(caps) => (&caps["count"]).parse::<u32>().unwrap(),
-        None => 0,
-    };
+    let draws = line.text[colon + 1..].split(';');
+    let draws = draws.map(|draw| parse_draw(draw, line)).collect::<Result<Vec<Draw>>>()?;
 
-    let blue: u32 = match BLUE_RE.captures(text) {
-        Some(caps) => (&caps["count"]).parse::<u32>().unwrap(),
-        None => 0,
-    };
+    Ok(Game{id, draws})
+}
+
+fn parse_draw(text: &str, line: Line) -> Result<Draw> {
+    let mut draw = Draw::default();
 
-    Draw {
-        red,
-        blue,
-        green,
+    for cubes in text.split(',') {
+        let (count, colour) = cubes.trim().split_once(' ')
+            .ok_or_else(|| line.error(cubes.trim_start(), "expected a number of cubes and a colour, such as \"3 blue\""))?;
+        let count = line.parse::<u32>(count)?;
+        *draw.cubes.entry(colour.trim().to_string()).or_insert(0) += count;
     }
+
+    Ok(draw)
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
+pub(crate) fn solve_part2(games: &[Game], limits: &CubeLimits)  -> String {
 
     let mut total = 0;
     
-    for line in input {
-        let g = parse_game(line.unwrap());
-        let min_counts = g.min_cubes_of_each_color();
+    for g in games {
+        let min_counts = g.min_cubes_of_each_color(limits);
         total += min_counts.power();
     }
 
@@ -125,13 +252,104 @@ pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
 mod tests {
     use super::*;
 
+    fn draw(cubes: &[(&str, u32)]) -> Draw {
+        Draw { cubes: cubes.iter().map(|(colour, count)| (colour.to_string(), *count)).collect() }
+    }
+
     #[test]
     fn test_parse_draw() {
-        let draw = parse_draw("2 red, 1 green, 5 blue");
-        assert_eq!(draw, Draw{red:2, green:1, blue:5});
+        let line = Line::new(1, "2 red, 1 green, 5 blue");
+        let actual = parse_draw(line.text, line).unwrap();
+        assert_eq!(draw(&[("red", 2), ("green", 1), ("blue", 5)]), actual);
+
+        let line = Line::new(1, "20 red");
+        let actual = parse_draw(line.text, line).unwrap();
+        assert_eq!(draw(&[("red", 20)]), actual);
+        assert_eq!(None, actual.cubes.get("green"));
+
+        let line = Line::new(1, " 3 mauve, 4 teal");
+        let actual = parse_draw(line.text, line).unwrap();
+        assert_eq!(draw(&[("mauve", 3), ("teal", 4)]), actual);
+    }
+
+    #[test]
+    fn test_cube_limits() {
+        let limits: CubeLimits = "mauve=3, teal=10".parse().unwrap();
+        assert_eq!(3, limits.limit("mauve"));
+        assert_eq!(0, limits.limit("red"));
+        assert_eq!(14, CubeLimits::default().limit("blue"));
+
+        assert!("red=12,green".parse::<CubeLimits>().is_err());
+        assert!("red=lots".parse::<CubeLimits>().is_err());
+    }
+
+    #[test]
+    fn test_custom_colours() {
+        let games: Vec<Game> = error::lines("Game 1: 3 mauve, 4 teal; 1 mauve\nGame 2: 5 teal; 1 red\nGame 3: 11 teal")
+            .map(parse_game)
+            .collect::<Result<_>>()
+            .unwrap();
+        let limits: CubeLimits = "mauve=3,teal=10".parse().unwrap();
+
+        // game 2 shows a red cube, and there are none in the bag
+        assert!(games[0].is_valid(&limits));
+        assert!(!games[1].is_valid(&limits));
+        assert!(!games[2].is_valid(&limits));
+        assert_eq!("1", solve_part1(&games, &limits));
+
+        assert_eq!(draw(&[("mauve", 3), ("teal", 4)]), games[0].min_cubes_of_each_color(&limits));
+        assert_eq!(12, games[0].min_cubes_of_each_color(&limits).power());
+    }
+
+    #[test]
+    fn test_configure() {
+        let mut settings = Settings::default();
+        assert!(Day02::new().configure(&settings).unwrap().is_none());
+
+        settings.set("cubes", "mauve=3,teal=10");
+        let day02 = Day02::new().configure(&settings).unwrap().unwrap();
+        assert_eq!(Some("mauve=3,teal=10".parse().unwrap()), day02.limits);
+
+        settings.set("cubes", "mauve");
+        let err = Day02::new().configure(&settings).err().unwrap();
+        assert_eq!("expected cube limits such as red=12,green=13,blue=14, found \"mauve\"", err.to_string());
+    }
+
+    #[test]
+    fn test_power_of_missing_colours() {
+        // a game that never shows blue needs no blue cubes, so its power is 0
+        let game = parse_game(Line::new(1, "Game 1: 3 red, 2 green")).unwrap();
+        let limits = CubeLimits::default();
+        assert_eq!(draw(&[("red", 3), ("green", 2), ("blue", 0)]), game.min_cubes_of_each_color(&limits));
+        assert_eq!("0", solve_part2(&[game], &limits));
+    }
+
+    #[test]
+    fn test_violations() {
+        let game = parse_game(Line::new(3, "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red, 15 blue")).unwrap();
+        let limits = CubeLimits::default();
+
+        let expected = vec!(
+            Violation { draw: 0, colour: "red".to_string(), count: 20, limit: 12 },
+            Violation { draw: 2, colour: "blue".to_string(), count: 15, limit: 14 },
+        );
+        assert_eq!(expected, game.violations(&limits));
+        assert_eq!("draw 1 shows 20 red, but the bag only holds 12", expected[0].to_string());
+
+        let explanation = explain_games(&[game], &limits);
+        assert_eq!("Game 3: impossible\n  draw 1 shows 20 red, but the bag only holds 12\n  draw 3 shows 15 blue, but the bag only holds 14\n", explanation);
+    }
+
+    #[test]
+    fn test_parse_game_errors() {
+        let err = parse_game(Line::new(4, "Game 4 3 blue, 4 red")).unwrap_err();
+        assert_eq!("line 4, column 1: expected \"Game <id>: <draws>\"", err.to_string());
+
+        let err = parse_game(Line::new(2, "Game x: 3 blue")).unwrap_err();
+        assert_eq!("line 2, column 6: expected a number, found \"x\"", err.to_string());
 
-        let draw = parse_draw("20 red");
-        assert_eq!(draw, Draw{red:20, green:0, blue:0});
+        let err = parse_game(Line::new(3, "Game 3: 3 blue, red")).unwrap_err();
+        assert_eq!("line 3, column 17: expected a number of cubes and a colour, such as \"3 blue\"", err.to_string());
     }
 }
 
@@ -1,8 +1,11 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+
+use std::collections::HashMap;
+
+use crate::error::{ self, Error, Line, Result };
+use crate::solver::Solver;
 
 #[derive(Debug)]
-struct Grid {
+pub struct Grid {
     data: Vec<String>,
     height: usize,
     width: usize,
@@ -21,7 +24,7 @@ impl Grid {
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct GridNumber {
+pub struct GridNumber {
     value: u32,
     row: usize,
     start_col: usize,
@@ -29,39 +32,156 @@ struct GridNumber {
 }
 
 impl GridNumber {
-    fn is_adjacent(&self, r: usize, c: usize) -> bool {
-        if r == self.row {
-            return c == self.end_col + 1 || c + 1 == self.start_col;
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    /// The cells around the number that are inside the grid, including the
+    /// diagonals.
+    fn neighbours(&self, g: &Grid) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let rows = self.row.saturating_sub(1)..=(self.row + 1).min(g.height - 1);
+        let cols = self.start_col.saturating_sub(1)..=(self.end_col + 1).min(g.width - 1);
+        rows.flat_map(move |r| cols.clone().map(move |c| (r, c)))
+            .filter(|&(r, c)| r != self.row || c < self.start_col || c > self.end_col)
+    }
+}
+
+/// Anything in the schematic that is not a digit or a `.`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Symbol {
+    pub glyph: char,
+    pub row: usize,
+    pub col: usize,
+}
+
+/// Which numbers touch which symbols, built once from the grid so that each
+/// question about them is answered without searching the grid again.
+/// Numbers and symbols are referred to by their index, in reading order.
+#[derive(Debug)]
+pub struct Adjacency {
+    numbers: Vec<GridNumber>,
+    symbols: Vec<Symbol>,
+    symbol_positions: HashMap<(usize, usize), usize>,
+    numbers_by_symbol: Vec<Vec<usize>>,
+    symbols_by_number: Vec<Vec<usize>>,
+}
+
+impl Adjacency {
+    pub fn new(grid: &Grid, numbers: Vec<GridNumber>) -> Self {
+        let mut symbols = vec!();
+        let mut symbol_positions = HashMap::new();
+        for (row, line) in grid.data.iter().enumerate() {
+            for (col, glyph) in line.char_indices().filter(|(_, c)| is_symbol(*c)) {
+                symbol_positions.insert((row, col), symbols.len());
+                symbols.push(Symbol { glyph, row, col });
+            }
         }
-        if r + 1 == self.row || r == self.row + 1 {
-            if c >= self.start_col {
-                return c <= self.end_col + 1;
+
+        let mut numbers_by_symbol = vec!(vec!(); symbols.len());
+        let mut symbols_by_number = vec!(vec!(); numbers.len());
+        for (n, number) in numbers.iter().enumerate() {
+            for position in number.neighbours(grid) {
+                if let Some(&s) = symbol_positions.get(&position) {
+                    numbers_by_symbol[s].push(n);
+                    symbols_by_number[n].push(s);
+                }
             }
-            return c + 1 == self.start_col;
         }
-        false
+
+        Adjacency { numbers, symbols, symbol_positions, numbers_by_symbol, symbols_by_number }
+    }
+
+    pub fn numbers(&self) -> &[GridNumber] {
+        &self.numbers
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// The index of the symbol at `row` and `col`, if there is one.
+    pub fn symbol_at(&self, row: usize, col: usize) -> Option<usize> {
+        self.symbol_positions.get(&(row, col)).copied()
+    }
+
+    pub fn numbers_touching(&self, symbol: usize) -> impl Iterator<Item = &GridNumber> {
+        self.numbers_by_symbol[symbol].iter().map(|&n| &self.numbers[n])
+    }
+
+    pub fn symbols_touching(&self, number: usize) -> impl Iterator<Item = &Symbol> {
+        self.symbols_by_number[number].iter().map(|&s| &self.symbols[s])
+    }
+
+    /// The numbers that touch at least one symbol.
+    pub fn part_numbers(&self) -> impl Iterator<Item = &GridNumber> {
+        self.numbers.iter().enumerate()
+            .filter(|(n, _)| !self.symbols_by_number[*n].is_empty())
+            .map(|(_, number)| number)
+    }
+
+    /// The symbols drawn as `glyph` that touch exactly `count` numbers.
+    pub fn gears(&self, glyph: char, count: usize) -> impl Iterator<Item = usize> + '_ {
+        self.symbols.iter().enumerate()
+            .filter(move |(s, symbol)| symbol.glyph == glyph && self.numbers_by_symbol[*s].len() == count)
+            .map(|(s, _)| s)
     }
 }
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    let grid = Grid::from_string_vec(data);
+pub struct Day03;
+
+impl Solver for Day03 {
+    type Input = Adjacency;
+
+    fn day(&self) -> u32 {
+        3
+    }
+
+    fn title(&self) -> &'static str {
+        "Gear Ratios"
+    }
+
+    fn parse(&self, input: &str) -> Result<Adjacency> {
+        let (grid, grid_numbers) = parse_input(input)?;
+        Ok(Adjacency::new(&grid, grid_numbers))
+    }
+
+    fn part1(&self, adjacency: &Adjacency) -> Result<String> {
+        Ok(solve_part1(adjacency))
+    }
+
+    fn part2(&self, adjacency: &Adjacency) -> Result<String> {
+        Ok(solve_part2(adjacency))
+    }
+}
+
+fn parse_input(input: &str) -> Result<(Grid, Vec<GridNumber>)> {
+    let width = input.lines().next().ok_or_else(|| Error::parse(1, 1, "input is empty"))?.len();
 
     let mut grid_numbers: Vec<GridNumber> = vec!();
 
-    for (row_num, line) in grid.data.iter().enumerate() {
-        grid_numbers.append(&mut extract_part_numbers_from_row(line, row_num));
+    for line in error::lines(input) {
+        if line.text.len() != width {
+            return Err(line.error(line.text, format!("expected a row of width {}", width)));
+        }
+        grid_numbers.append(&mut extract_part_numbers_from_row(line)?);
     }
-    
-    let ttl: u32 = grid_numbers.iter()
-        .filter(|x| is_part_number(x, &grid))
+
+    let data: Vec<String> = input.lines().map(String::from).collect();
+    let grid = Grid::from_string_vec(data);
+
+    Ok((grid, grid_numbers))
+}
+
+pub(crate) fn solve_part1(adjacency: &Adjacency)  -> String {
+    let ttl: u32 = adjacency.part_numbers()
         .map(|x| x.value)
         .sum();
     ttl.to_string()
 }
 
-fn extract_part_numbers_from_row(line: &str, row_num: usize) -> Vec<GridNumber> {
+fn extract_part_numbers_from_row(row: Line) -> Result<Vec<GridNumber>> {
+    let line = row.text;
+    let row_num = row.number - 1;
     let mut part_numbers: Vec<GridNumber> = vec!();
 
     let mut parsing = false;
@@ -80,7 +200,7 @@ fn extract_part_numbers_from_row(line: &str, row_num: usize) -> Vec<GridNumber>
             '\0'.. => {
                 if parsing {
                     // reached the end of a number
-                    let value = line[s..i].parse::<u32>().unwrap();
+                    let value = row.parse::<u32>(&line[s..i])?;
                     part_numbers.push(GridNumber {
                         value,
                         row: row_num,
@@ -96,7 +216,7 @@ fn extract_part_numbers_from_row(line: &str, row_num: usize) -> Vec<GridNumber>
     }
 
     if parsing {
-        let value = line[s..].parse::<u32>().unwrap();
+        let value = row.parse::<u32>(&line[s..])?;
         part_numbers.push(GridNumber {
             value,
             row: row_num,
@@ -105,45 +225,7 @@ fn extract_part_numbers_from_row(line: &str, row_num: usize) -> Vec<GridNumber>
         });
     }
 
-    return part_numbers;
-}
-
-fn is_part_number(n: &GridNumber, g: &Grid) -> bool {
-    let max_col = g.width - 1;
-    let max_row = g.height - 1;
-    
-    let startcol = if n.start_col > 0 { n.start_col-1} else { 0 };
-    let endcol = max_col.min(n.end_col + 1);
-
-    if n.row > 0 {
-        let prev_row = g.data.get(n.row-1).unwrap();
-
-        for c in prev_row[startcol..=endcol].chars() {
-            if is_symbol(c) {
-                return true;
-            }
-        }
-    }
-
-    if n.row < max_row {
-        let next_row = g.data.get(n.row+1).unwrap();
-        for c in next_row[startcol..=endcol].chars() {
-            if is_symbol(c) {
-                return true;
-            }
-        }
-    }
-
-    // a little wasteful, iterating over the number itself
-    let curr_row = g.data.get(n.row).unwrap();
-    for c in curr_row[startcol..=endcol].chars() {
-        if is_symbol(c) {
-            return true;
-        }
-    }
-
-    return false;
-
+    return Ok(part_numbers);
 }
 
 fn is_symbol(c: char) -> bool {
@@ -154,46 +236,22 @@ fn is_symbol(c: char) -> bool {
     }
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
-
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    let grid = Grid::from_string_vec(data);
-
-    let mut grid_numbers: Vec<GridNumber> = vec!();
-    for (row_num, line) in grid.data.iter().enumerate() {
-        grid_numbers.append(&mut extract_part_numbers_from_row(line, row_num));
-    }
-    
-    let mut ttl: u32 = 0;
-    for (r, row) in grid.data.iter().enumerate() {
-        for (c, x) in row.char_indices() {
-            if x == '*' {
-                let adjacents = adjacent_values(r, c, &grid_numbers);
-                if adjacents.len() == 2 {
-                    ttl += adjacents.iter().product::<u32>();
-                }
-            }
-        }
-    }
+pub(crate) fn solve_part2(adjacency: &Adjacency)  -> String {
+    let ttl: u32 = adjacency.gears('*', 2)
+        .map(|gear| adjacency.numbers_touching(gear).map(|x| x.value).product::<u32>())
+        .sum();
 
     ttl.to_string()
 }
 
-fn adjacent_values(r: usize, c: usize, g: &Vec<GridNumber>) -> Vec<u32> {
-    g.iter()
-        .filter(|x| x.is_adjacent(r, c))
-        .map(|x| x.value)
-        .collect()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_parse_row() {
-        let row = "467..11...0";
-        let parts: Vec<GridNumber> = extract_part_numbers_from_row(row, 0);
+        let row = Line::new(1, "467..11...0");
+        let parts: Vec<GridNumber> = extract_part_numbers_from_row(row).unwrap();
 
         assert_eq!(3, parts.len());
         let first = parts.first().unwrap();
@@ -222,55 +280,63 @@ mod tests {
         });
     }
 
+    fn example() -> Adjacency {
+        let (grid, grid_numbers) = parse_input("467..114..\n...*......\n..35..633.\n......#...\n617*......").unwrap();
+        Adjacency::new(&grid, grid_numbers)
+    }
+
     #[test]
     fn test_is_part_number() {
-        let data = vec!(
-            String::from("467..114.."),
-            String::from("...*......"),
-            String::from("..35..633."),
-            String::from("......#..."),
-            String::from("617*......"),
-        );
-        let grid = Grid::from_string_vec(data);
-
-        let grid_number = GridNumber {
-            value: 467,
-            row: 0,
-            start_col: 0,
-            end_col: 2,
-        };
-        assert!(is_part_number(&grid_number, &grid));
-
-        let grid_number = GridNumber {
-            value: 633,
-            row: 2,
-            start_col: 6,
-            end_col: 8,
-        };
-        assert!(is_part_number(&grid_number, &grid));
-
-        let grid_number = GridNumber {
-            value: 617,
-            row: 4,
-            start_col: 0,
-            end_col: 2,
-        };
-        assert!(is_part_number(&grid_number, &grid));
-
-        let grid_number = GridNumber {
-            value: 35,
-            row: 2,
-            start_col: 2,
-            end_col: 3,
-        };
-        assert!(is_part_number(&grid_number, &grid));
-
-        let grid_number = GridNumber {
-            value: 114,
-            row: 0,
-            start_col: 5,
-            end_col: 7,
-        };
-        assert!(!is_part_number(&grid_number, &grid));
+        let adjacency = example();
+
+        let part_numbers: Vec<u32> = adjacency.part_numbers().map(GridNumber::value).collect();
+        assert_eq!(vec!(467, 35, 633, 617), part_numbers);
+
+        // 114 touches nothing
+        assert_eq!(114, adjacency.numbers()[1].value);
+        assert_eq!(0, adjacency.symbols_touching(1).count());
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let adjacency = example();
+        assert_eq!(3, adjacency.symbols().len());
+
+        let hash = adjacency.symbol_at(3, 6).unwrap();
+        assert_eq!(Symbol { glyph: '#', row: 3, col: 6 }, adjacency.symbols()[hash]);
+        assert_eq!(None, adjacency.symbol_at(3, 5));
+
+        let touching: Vec<u32> = adjacency.numbers_touching(hash).map(GridNumber::value).collect();
+        assert_eq!(vec!(633), touching);
+        let touching: Vec<&Symbol> = adjacency.symbols_touching(3).collect();
+        assert_eq!(vec!(&adjacency.symbols()[hash]), touching);
+
+        let gear = adjacency.symbol_at(1, 3).unwrap();
+        let touching: Vec<u32> = adjacency.numbers_touching(gear).map(GridNumber::value).collect();
+        assert_eq!(vec!(467, 35), touching);
+
+        assert_eq!(vec!(gear), adjacency.gears('*', 2).collect::<Vec<usize>>());
+        assert_eq!(vec!(adjacency.symbol_at(4, 3).unwrap()), adjacency.gears('*', 1).collect::<Vec<usize>>());
+        assert_eq!(0, adjacency.gears('#', 2).count());
+    }
+
+    #[test]
+    fn test_edges() {
+        // numbers on the edges of the grid, touching symbols in the corners
+        let (grid, grid_numbers) = parse_input("1*.\n..2\n.#3").unwrap();
+        let adjacency = Adjacency::new(&grid, grid_numbers);
+        let part_numbers: Vec<u32> = adjacency.part_numbers().map(GridNumber::value).collect();
+        assert_eq!(vec!(1, 2, 3), part_numbers);
+        assert_eq!(2, adjacency.numbers_touching(0).count());
+        assert_eq!(2, adjacency.numbers_touching(1).count());
+    }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input("467..\n...*\n").unwrap_err();
+        assert_eq!("line 2, column 1: expected a row of width 5", err.to_string());
+
+        let err = parse_input("").unwrap_err();
+        assert_eq!("line 1, column 1: input is empty", err.to_string());
     }
 }
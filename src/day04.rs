@@ -1,48 +1,47 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
 
-struct Card {
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::error::{ self, Error, Line, Result };
+use crate::runner::Format;
+use crate::solver::Solver;
+
+pub struct Card {
+    id: u32,
     winning_numbers: Vec<u32>,
     having_numbers: Vec<u32>,
 }
 
 impl Card {
-    fn from_string_input(s: &str) -> Self {
-        let colon_index = s.find(":").expect("Bad input. No Card ID");
+    fn from_string_input(line: Line) -> Result<Self> {
+        let s = line.text;
+        let colon_index = s.find(":").ok_or_else(|| line.error(s, "bad input, no card ID"))?;
+        let id = s[..colon_index].split_whitespace().last()
+            .ok_or_else(|| line.error(s, "bad input, no card ID"))?;
+        let id = line.parse::<u32>(id)?;
         let rest = &s[colon_index+1..];
 
-        let pipe_index = rest.find("|").expect("Bad input. No pipe delimiter.");
+        let pipe_index = rest.find("|").ok_or_else(|| line.error(rest, "bad input, no pipe delimiter"))?;
         let (winning, having) = rest.split_at(pipe_index);
 
-        let winning_numbers: Vec<u32> = winning.split(" ")
-            .map(|x| x.parse::<u32>())
-            .filter(|x| x.is_ok())
-            .map(|x| x.unwrap())
-            .collect();
+        let winning_numbers: Vec<u32> = winning.split_whitespace()
+            .map(|x| line.parse::<u32>(x))
+            .collect::<Result<_>>()?;
 
-        let having_numbers: Vec<u32> = having.split(" ")
-            .map(|x| x.parse::<u32>())
-            .filter(|x| x.is_ok())
-            .map(|x| x.unwrap())
-            .collect();
+        let having_numbers: Vec<u32> = having[1..].split_whitespace()
+            .map(|x| line.parse::<u32>(x))
+            .collect::<Result<_>>()?;
         
-        Card {winning_numbers, having_numbers}
+        Ok(Card {id, winning_numbers, having_numbers})
     }
 
+    /// How many different numbers we have are also winning numbers. A number
+    /// that is repeated on either side of the card only counts once.
     fn matching_numbers(&self) -> u32 {
-        // this procedure assumes there are no duplicate numbers in
-        // either the winning numbers or having numbers
-
-        let mut all_nums = self.winning_numbers.clone();
-        all_nums.append(&mut self.having_numbers.clone());
-
-        let total_nums = all_nums.len();
-        
-        all_nums.sort_unstable();
-        all_nums.dedup();
-        let deduped_nums = all_nums.len();
+        let winning: HashSet<u32> = self.winning_numbers.iter().copied().collect();
+        let having: HashSet<u32> = self.having_numbers.iter().copied().collect();
 
-        u32::try_from(total_nums - deduped_nums).unwrap()
+        u32::try_from(winning.intersection(&having).count()).unwrap()
     }
 
     fn points(&self) -> u32 {
@@ -55,37 +54,120 @@ impl Card {
     }
 }
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
+pub struct Day04;
+
+impl Solver for Day04 {
+    type Input = Vec<Card>;
+
+    fn day(&self) -> u32 {
+        4
+    }
+
+    fn title(&self) -> &'static str {
+        "Scratchcards"
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<Card>> {
+        error::lines(input).map(Card::from_string_input).collect()
+    }
+
+    fn part1(&self, cards: &Vec<Card>) -> Result<String> {
+        Ok(solve_part1(cards))
+    }
+
+    fn part2(&self, cards: &Vec<Card>) -> Result<String> {
+        solve_part2(cards)
+    }
+
+    fn explain(&self, cards: &Vec<Card>, format: Format) -> Option<String> {
+        if format != Format::Text {
+            return None;
+        }
+        match Cascade::new(cards) {
+            Ok(cascade) => Some(format!("{}\n", cascade)),
+            Err(err) => Some(format!("{}\n", err)),
+        }
+    }
+}
+
+pub(crate) fn solve_part1(cards: &[Card])  -> String {
     let mut ttl = 0;
-    for line in input {
-        let card = Card::from_string_input(&line.unwrap());
+    for card in cards {
         ttl += card.points();
     }
 
     ttl.to_string()
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
-    let cards: Vec<Card> = input.map(|x| Card::from_string_input(&x.unwrap())).collect();
+/// How many copies of one card we end up with, and where they came from.
+#[derive(Debug, PartialEq)]
+pub struct CardCopies {
+    pub id: u32,
+    pub matches: u32,
+    /// Copies of this card, counting the original.
+    pub copies: u32,
+    /// The cards that won copies of this one, and how many each won.
+    pub won_from: Vec<(u32, u32)>,
+}
+
+/// The copies of every card won in part 2, in the order of the table.
+#[derive(Debug)]
+pub struct Cascade {
+    pub cards: Vec<CardCopies>,
+}
 
-    // to start, we have 1 of each card
-    let mut card_counts: Vec<u32> = vec![1; cards.len()];
+impl Cascade {
+    pub fn new(cards: &[Card]) -> Result<Self> {
+        // to start, we have 1 of each card
+        let mut copies: Vec<CardCopies> = cards.iter()
+            .map(|card| CardCopies { id: card.id, matches: card.matching_numbers(), copies: 1, won_from: vec!() })
+            .collect();
+
+        for i in 0..copies.len() {
+            let matching_numbers = usize::try_from(copies[i].matches).expect("Number of cards exceeds usize");
+            let (id, copies_of_current_card) = (copies[i].id, copies[i].copies);
+
+            let lbound = i+1;
+            let ubound = i + matching_numbers;
+            if ubound >= cards.len() {
+                return Err(Error::solve(format!("card {} wins copies of cards past the end of the table", id)));
+            }
+            for won in &mut copies[lbound..=ubound] {
+                won.copies += copies_of_current_card;
+                won.won_from.push((id, copies_of_current_card));
+            }
+        }
 
-    for (i, card) in cards.iter().enumerate() {
-        let matching_numbers = card.matching_numbers();
-        let matching_numbers = usize::try_from(matching_numbers).expect("Number of cards exceeds usize");
+        Ok(Cascade { cards: copies })
+    }
 
-        let copies_of_current_card = card_counts[i];
+    pub fn total(&self) -> u32 {
+        self.cards.iter().map(|card| card.copies).sum()
+    }
+}
 
-        let lbound = i+1;
-        let ubound = i + matching_numbers;
-        for i in lbound..=ubound {
-            card_counts[i] += copies_of_current_card;
+impl fmt::Display for Cascade {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for card in &self.cards {
+            write!(f, "Card {}: {} matching, {} {}", card.id, card.matches, card.copies, plural(card.copies, "copy", "copies"))?;
+            if !card.won_from.is_empty() {
+                let won_from: Vec<String> = card.won_from.iter()
+                    .map(|(id, copies)| format!("{} from card {}", copies, id))
+                    .collect();
+                write!(f, " (the original, {})", won_from.join(", "))?;
+            }
+            writeln!(f)?;
         }
+        write!(f, "{} cards in total", self.total())
     }
+}
+
+fn plural<'a>(n: u32, one: &'a str, many: &'a str) -> &'a str {
+    if n == 1 { one } else { many }
+}
 
-    let ttl_number_of_cards: u32 = card_counts.iter().sum();
-    ttl_number_of_cards.to_string()
+pub(crate) fn solve_part2(cards: &[Card])  -> Result<String> {
+    Ok(Cascade::new(cards)?.total().to_string())
 }
 
 #[cfg(test)]
@@ -94,23 +176,74 @@ mod tests {
 
     #[test]
     fn test_card_constructor() {
-        let card = Card::from_string_input("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53");
+        let card = Card::from_string_input(Line::new(1, "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53")).unwrap();
         let expected_winning = vec!(41, 48, 83, 86, 17);
         let expected_having = vec!(83, 86, 6, 31, 17, 9, 48, 53);
 
+        assert_eq!(card.id, 1);
         assert_eq!(card.winning_numbers, expected_winning);
         assert_eq!(card.having_numbers, expected_having);
     }
 
     #[test]
     fn test_card_points() {
-        let card = Card::from_string_input("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53");
+        let card = Card::from_string_input(Line::new(1, "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53")).unwrap();
         assert_eq!(8, card.points());
 
-        let card = Card::from_string_input("Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19");
+        let card = Card::from_string_input(Line::new(1, "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19")).unwrap();
         assert_eq!(2, card.points());
 
-        let card = Card::from_string_input("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36");
+        let card = Card::from_string_input(Line::new(1, "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36")).unwrap();
         assert_eq!(0, card.points());
     }
+
+    #[test]
+    fn test_duplicate_numbers() {
+        // the old sort and dedup approach counted 41 and 17 twice, and the
+        // repeated 5 as a match
+        let card = Card::from_string_input(Line::new(1, "Card  12: 41 41 5 5 17 | 41 17 17 6")).unwrap();
+        assert_eq!(12, card.id);
+        assert_eq!(2, card.matching_numbers());
+        assert_eq!(2, card.points());
+    }
+
+    #[test]
+    fn test_cascade() {
+        let cards: Vec<Card> = error::lines("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11")
+            .map(Card::from_string_input)
+            .collect::<Result<_>>()
+            .unwrap();
+
+        let cascade = Cascade::new(&cards).unwrap();
+        assert_eq!(30, cascade.total());
+        assert_eq!(
+            CardCopies { id: 5, matches: 0, copies: 14, won_from: vec!((1, 1), (3, 4), (4, 8)) },
+            cascade.cards[4]);
+
+        let explanation = cascade.to_string();
+        let lines: Vec<&str> = explanation.lines().collect();
+        assert_eq!("Card 1: 4 matching, 1 copy", lines[0]);
+        assert_eq!("Card 3: 2 matching, 4 copies (the original, 1 from card 1, 2 from card 2)", lines[2]);
+        assert_eq!("30 cards in total", lines[6]);
+
+        let err = Cascade::new(&cards[..2]).err().unwrap();
+        assert_eq!("card 1 wins copies of cards past the end of the table", err.to_string());
+    }
+
+    #[test]
+    fn test_card_constructor_errors() {
+        let err = Card::from_string_input(Line::new(3, "Card 3: 1 21 53 59 44 69 82 63 72 16 21 14  1")).err().unwrap();
+        assert_eq!("line 3, column 8: bad input, no pipe delimiter", err.to_string());
+
+        let err = Card::from_string_input(Line::new(1, "Card 1: 41 48 | 83 8b6")).err().unwrap();
+        assert_eq!("line 1, column 20: expected a number, found \"8b6\"", err.to_string());
+
+        let err = Card::from_string_input(Line::new(2, "Card two: 41 48 | 83 86")).err().unwrap();
+        assert_eq!("line 2, column 6: expected a number, found \"two\"", err.to_string());
+    }
 }
@@ -1,14 +1,37 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
 
-#[derive(Debug)]
-struct MapEntry {
+use std::ops::Range;
+
+use crate::error::{ self, Error, Line, Result };
+use crate::solver::Solver;
+
+/// One line of a map: `range_len` numbers starting at `src_range_start`
+/// map to the same number of numbers starting at `dst_range_start`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapEntry {
     src_range_start: u64,
     dst_range_start: u64,
     range_len: u64,
 }
 
 impl MapEntry {
+    /// An entry written in the almanac's order: destination start, source
+    /// start, then length. Neither range may run past `u64::MAX`.
+    pub fn new(dst_range_start: u64, src_range_start: u64, range_len: u64) -> Result<Self> {
+        if dst_range_start.checked_add(range_len).is_none() || src_range_start.checked_add(range_len).is_none() {
+            return Err(Error::solve("map entry runs past the largest possible number"));
+        }
+        Ok(MapEntry { src_range_start, dst_range_start, range_len })
+    }
+
+    /// An entry mapping `range` to itself.
+    fn identity(range: Range<u64>) -> Self {
+        MapEntry { src_range_start: range.start, dst_range_start: range.start, range_len: range.end - range.start }
+    }
+
+    fn src_end(&self) -> u64 {
+        self.src_range_start + self.range_len
+    }
+
     fn lookup(&self, source: u64) -> Option<u64> {
 
         if source < self.src_range_start {
@@ -22,151 +45,495 @@ impl MapEntry {
         let diff = source - self.src_range_start;
         return Option::Some(self.dst_range_start + diff);
     }
+
+    /// Split `range` into the part covered by this entry, mapped to its
+    /// destination, and the parts on either side that this entry leaves
+    /// alone.
+    fn lookup_range(&self, range: &Range<u64>) -> (Option<Range<u64>>, Vec<Range<u64>>) {
+        let start = range.start.max(self.src_range_start);
+        let end = range.end.min(self.src_end());
+        if start >= end {
+            return (Option::None, vec!(range.clone()));
+        }
+
+        let mut rest = vec!();
+        if range.start < start {
+            rest.push(range.start..start);
+        }
+        if end < range.end {
+            rest.push(end..range.end);
+        }
+
+        let mapped = (self.dst_range_start + (start - self.src_range_start))..(self.dst_range_start + (end - self.src_range_start));
+        return (Option::Some(mapped), rest);
+    }
+
+    /// The entry mapping this one's destination back to its source.
+    fn inverse(&self) -> MapEntry {
+        MapEntry {
+            src_range_start: self.dst_range_start,
+            dst_range_start: self.src_range_start,
+            range_len: self.range_len,
+        }
+    }
+}
+
+/// The entries of one map, sorted by source and checked not to overlap.
+/// Numbers that no entry covers map to themselves.
+#[derive(Debug, Default)]
+pub struct Mapping {
+    entries: Vec<MapEntry>,
 }
 
-type Mapping = Vec<MapEntry>;
+impl Mapping {
+    /// Sort and check `entries`, which must not overlap. Entries of length 0
+    /// cover nothing and are dropped.
+    pub fn new(entries: Vec<MapEntry>) -> Result<Self> {
+        Mapping::sorted(entries.clone()).map_err(|(first, second)| {
+            let range = |e: &MapEntry| e.src_range_start..e.src_end();
+            Error::solve(format!("the map entry for {:?} overlaps the one for {:?}",
+                range(&entries[second]), range(&entries[first])))
+        })
+    }
+
+    /// Like `new`, but if two entries overlap their indexes in `entries` are
+    /// returned instead, for the parser to turn into line numbers.
+    fn sorted(entries: Vec<MapEntry>) -> std::result::Result<Self, (usize, usize)> {
+        let mut order: Vec<usize> = (0..entries.len())
+            .filter(|&i| entries[i].range_len > 0)
+            .collect();
+        order.sort_by_key(|&i| entries[i].src_range_start);
+
+        for pair in order.windows(2) {
+            if entries[pair[0]].src_end() > entries[pair[1]].src_range_start {
+                return Err((pair[0].min(pair[1]), pair[0].max(pair[1])));
+            }
+        }
+
+        Ok(Mapping { entries: order.iter().map(|&i| entries[i]).collect() })
+    }
 
-struct Almanac {
+    /// The numbers between the entries that no entry covers.
+    pub fn gaps(&self) -> Vec<Range<u64>> {
+        self.entries.windows(2)
+            .map(|pair| pair[0].src_end()..pair[1].src_range_start)
+            .filter(|range| !range.is_empty())
+            .collect()
+    }
+
+    pub fn lookup(&self, source: u64) -> u64 {
+        // the last entry starting at or before `source` is the only one that
+        // can cover it
+        let i = self.entries.partition_point(|e| e.src_range_start <= source);
+        return i.checked_sub(1)
+            .and_then(|i| self.entries[i].lookup(source))
+            .unwrap_or(source);
+    }
+
+    /// Map whole ranges, splitting them where they cross entry boundaries.
+    pub fn lookup_ranges(&self, ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+        let mut mapped = vec!();
+        let mut pending = ranges;
+
+        // Each entry takes the pieces of the ranges it covers, and passes the
+        // rest on to the next entry.
+        for entry in &self.entries {
+            let mut unmapped = vec!();
+            for range in &pending {
+                let (hit, rest) = entry.lookup_range(range);
+                mapped.extend(hit);
+                unmapped.extend(rest);
+            }
+            pending = unmapped;
+        }
+
+        // whatever no entry covers maps to itself
+        mapped.extend(pending);
+        return mapped;
+    }
+
+    /// The ranges of numbers that map into `ranges`.
+    pub fn reverse_lookup_ranges(&self, ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+        // numbers that some entry maps into the ranges
+        let mut found: Vec<Range<u64>> = self.entries.iter()
+            .flat_map(|entry| ranges.iter().filter_map(move |range| entry.inverse().lookup_range(range).0))
+            .collect();
+
+        // numbers that no entry covers map to themselves
+        let mut uncovered = ranges;
+        for entry in &self.entries {
+            uncovered = uncovered.iter().flat_map(|range| entry.lookup_range(range).1).collect();
+        }
+
+        found.extend(uncovered);
+        return found;
+    }
+
+    /// This mapping as a list of entries covering every number below
+    /// `u64::MAX`, with the gaps filled by entries mapping to themselves.
+    fn pieces(&self) -> Vec<MapEntry> {
+        let mut pieces = vec!();
+        let mut covered_to = 0;
+        for entry in &self.entries {
+            if covered_to < entry.src_range_start {
+                pieces.push(MapEntry::identity(covered_to..entry.src_range_start));
+            }
+            pieces.push(*entry);
+            covered_to = entry.src_end();
+        }
+        if covered_to < u64::MAX {
+            pieces.push(MapEntry::identity(covered_to..u64::MAX));
+        }
+        return pieces;
+    }
+
+    /// A single mapping that does the work of this one followed by `next`.
+    pub fn compose(&self, next: &Mapping) -> Mapping {
+        let next_pieces = next.pieces();
+        let mut entries = vec!();
+
+        // Follow each piece of this mapping through the pieces of `next`
+        // that its destination overlaps.
+        for piece in self.pieces() {
+            let dst_end = piece.dst_range_start + piece.range_len;
+            for n in &next_pieces {
+                let start = piece.dst_range_start.max(n.src_range_start);
+                let end = dst_end.min(n.src_end());
+                if start >= end {
+                    continue;
+                }
+
+                let entry = MapEntry {
+                    src_range_start: piece.src_range_start + (start - piece.dst_range_start),
+                    dst_range_start: n.dst_range_start + (start - n.src_range_start),
+                    range_len: end - start,
+                };
+                if entry.src_range_start != entry.dst_range_start {
+                    entries.push(entry);
+                }
+            }
+        }
+
+        Mapping::sorted(entries).expect("Pieces of a mapping never overlap")
+    }
+}
+
+/// One `X-to-Y map:` section of the almanac, mapping numbers of category
+/// `from` to numbers of category `to`.
+#[derive(Debug)]
+struct Stage {
+    from: String,
+    to: String,
+    mapping: Mapping,
+    // line number of the section's header, for reporting errors
+    line: usize,
+}
+
+/// The seeds, and the stages that map them from one category to the next.
+/// The stages are ordered so that each one starts from the category the
+/// previous one ends in, e.g. seed -> soil -> ... -> location.
+pub struct Almanac {
     seeds: Vec<u64>,
-    soil_to_fertilizer_map: Mapping,
-    seed_to_soil_map: Mapping,
-    fertilizer_to_water_map: Mapping,
-    water_to_light_map: Mapping,
-    light_to_temperature_map: Mapping,
-    temperature_to_humidity_map: Mapping,
-    humidity_to_location_map: Mapping,
+    stages: Vec<Stage>,
 }
 
 impl Almanac {
-    fn empty() -> Self {
-        Almanac {
-            seeds: vec!(),
-            seed_to_soil_map: vec!(),
-            soil_to_fertilizer_map: vec!(),
-            fertilizer_to_water_map: vec!(),
-            water_to_light_map: vec!(),
-            light_to_temperature_map: vec!(),
-            temperature_to_humidity_map: vec!(),
-            humidity_to_location_map: vec!(),
+    /// The categories of the chain, in order.
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = self.stages.iter().map(|s| s.from.as_str()).collect();
+        categories.extend(self.stages.last().map(|s| s.to.as_str()));
+        return categories;
+    }
+
+    /// The stages needed to get from category `from` to category `to`.
+    fn stages_between(&self, from: &str, to: &str) -> Result<&[Stage]> {
+        let categories = self.categories();
+        let position = |category: &str| categories.iter().position(|c| *c == category)
+            .ok_or_else(|| Error::solve(format!("the almanac does not have a {:?} category", category)));
+
+        let (start, end) = (position(from)?, position(to)?);
+        if end < start {
+            return Err(Error::solve(format!("{:?} comes before {:?} in the almanac", to, from)));
         }
+        return Ok(&self.stages[start..end]);
     }
 
-    fn map_seed_to_location(&self, seed: u64) -> u64 {
-        let soil = lookup_helper(seed, &self.seed_to_soil_map);
-        let fertilizer = lookup_helper(soil, &self.soil_to_fertilizer_map);
-        let water = lookup_helper(fertilizer, &self.fertilizer_to_water_map);
-        let light = lookup_helper(water, &self.water_to_light_map);
-        let temp = lookup_helper(light, &self.light_to_temperature_map);
-        let humidity = lookup_helper(temp, &self.temperature_to_humidity_map);
-        let location = lookup_helper(humidity, &self.humidity_to_location_map);
-        return location;
+    /// Map a number of category `from` to category `to`, which must come
+    /// later in the chain.
+    pub fn map(&self, from: &str, to: &str, value: u64) -> Result<u64> {
+        let stages = self.stages_between(from, to)?;
+        return Ok(stages.iter().fold(value, |value, stage| stage.mapping.lookup(value)));
+    }
+
+    /// Map whole ranges of numbers of category `from` to the ranges of
+    /// category `to` that they end up in.
+    pub fn map_ranges(&self, from: &str, to: &str, ranges: Vec<Range<u64>>) -> Result<Vec<Range<u64>>> {
+        let stages = self.stages_between(from, to)?;
+        return Ok(stages.iter().fold(ranges, |ranges, stage| stage.mapping.lookup_ranges(ranges)));
+    }
+
+    /// Map a number of category `from` back to the numbers of the earlier
+    /// category `to` that map to it. A mapping can send several numbers to
+    /// the same place, so there may be more than one.
+    pub fn inverse(&self, from: &str, to: &str, value: u64) -> Result<Vec<u64>> {
+        let point = value..value + 1;
+        let mut values: Vec<u64> = self.inverse_ranges(from, to, vec!(point))?.iter()
+            .map(|range| range.start)
+            .collect();
+        values.sort();
+        return Ok(values);
     }
-}
 
-fn parse_input(input: Lines<BufReader<File>>) -> Almanac {
+    /// Map whole ranges of numbers of category `from` back to the ranges of
+    /// the earlier category `to` that map into them.
+    pub fn inverse_ranges(&self, from: &str, to: &str, ranges: Vec<Range<u64>>) -> Result<Vec<Range<u64>>> {
+        let stages = self.stages_between(to, from)?;
+        return Ok(stages.iter().rev().fold(ranges, |ranges, stage| stage.mapping.reverse_lookup_ranges(ranges)));
+    }
 
-    let mut almanac = Almanac::empty();
+    /// A single mapping from category `from` to category `to`, combining the
+    /// stages in between so that a lookup takes one step instead of one per
+    /// stage.
+    pub fn composed(&self, from: &str, to: &str) -> Result<Mapping> {
+        let stages = self.stages_between(from, to)?;
+        return Ok(stages.iter().fold(Mapping::default(), |mapping, stage| mapping.compose(&stage.mapping)));
+    }
 
-    let mut current_map_name = String::from("");
+    /// The lowest number of the last category (e.g. location) that can be
+    /// reached from `sources` of the first (e.g. seed). This works from the
+    /// other end to `map_ranges`: it searches for the smallest `n` such that
+    /// one of the sources maps to a number below `n`. It is slower, but
+    /// handy to cross-check answers.
+    pub fn lowest_reachable(&self, sources: &[Range<u64>]) -> Result<Option<u64>> {
+        let (first, last) = self.endpoints()?;
+        let reachable = |n: u64| -> Result<bool> {
+            let below = 0..n;
+            let found = self.inverse_ranges(last, first, vec!(below))?;
+            Ok(!intersect_ranges(&found, sources).is_empty())
+        };
 
-    for line in input {
-        let line = line.unwrap();
+        if !reachable(u64::MAX)? {
+            return Ok(None);
+        }
+
+        // the answer is the largest n for which nothing below it is reachable
+        let (mut low, mut high) = (0, u64::MAX);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if reachable(mid + 1)? {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        return Ok(Some(low));
+    }
+
+    /// The seeds, read as pairs of a start and a length, as in part 2.
+    fn seed_ranges(&self) -> Result<Vec<Range<u64>>> {
+        if !self.seeds.len().is_multiple_of(2) {
+            return Err(Error::solve("the seeds must be listed as pairs of a start and a length"));
+        }
+        // part 1 reads the same numbers as single seeds, so a pair that
+        // overflows only stops part 2
+        self.seeds.chunks_exact(2)
+            .map(|chunk| match chunk[0].checked_add(chunk[1]) {
+                Some(end) => Ok(chunk[0]..end),
+                None => Err(Error::solve(format!("the seed range starting at {} runs past the largest possible number", chunk[0]))),
+            })
+            .collect()
+    }
+
+    /// The first and last categories of the chain, e.g. seed and location.
+    fn endpoints(&self) -> Result<(&str, &str)> {
+        let categories = self.categories();
+        match (categories.first(), categories.last()) {
+            (Some(first), Some(last)) => Ok((first, last)),
+            _ => Err(Error::solve("the almanac does not contain any maps")),
+        }
+    }
+}
+
+fn parse_input(input: &str) -> Result<Almanac> {
+
+    let mut seeds = vec!();
+    let mut stages: Vec<Stage> = vec!();
+    // entries of the map being read, with their line numbers
+    let mut entries: Vec<(usize, MapEntry)> = vec!();
+    let mut in_map = false;
+
+    for l in error::lines(input) {
+        let line = l.text;
 
         if line.starts_with("seeds:") {
-            almanac.seeds = parse_seeds_line(&line);
+            seeds = parse_seeds_line(l)?;
             continue;
         }
 
         if line.contains("map:") {
-            current_map_name = line;
+            finish_map(stages.last_mut(), &mut entries)?;
+            let stage = parse_map_header(l)?;
+            if let Some(other) = stages.iter().find(|s| s.from == stage.from) {
+                return Err(l.error(line, format!("there is already a map from {:?}, on line {}", stage.from, other.line)));
+            }
+            stages.push(stage);
+            in_map = true;
             continue;
         }
 
         if line.len() < 2 {
-            current_map_name = String::from("");
+            finish_map(stages.last_mut(), &mut entries)?;
+            in_map = false;
             continue;
         }
 
 
-        let numbers: Vec<u64> = line.split(" ").map(|x| x.parse::<u64>().expect("Not a number!")).collect();
+        let numbers: Vec<u64> = line.split(" ").map(|x| l.parse::<u64>(x)).collect::<Result<_>>()?;
         if numbers.len() != 3 {
-            panic!("Unexpected map entry length");
+            return Err(l.error(line, "expected a map entry of 3 numbers"));
         }
 
-        let map_entry = MapEntry{
-            src_range_start: numbers[1],
-            dst_range_start: numbers[0],
-            range_len: numbers[2]
-        };
-
-        match current_map_name.as_str() {
-            "seed-to-soil map:" => almanac.seed_to_soil_map.push(map_entry),
-            "soil-to-fertilizer map:" => almanac.soil_to_fertilizer_map.push(map_entry),
-            "fertilizer-to-water map:" => almanac.fertilizer_to_water_map.push(map_entry),
-            "water-to-light map:" => almanac.water_to_light_map.push(map_entry),
-            "light-to-temperature map:" => almanac.light_to_temperature_map.push(map_entry),
-            "temperature-to-humidity map:" => almanac.temperature_to_humidity_map.push(map_entry),
-            "humidity-to-location map:" => almanac.humidity_to_location_map.push(map_entry),
+        let map_entry = MapEntry::new(numbers[0], numbers[1], numbers[2])
+            .map_err(|err| l.error(line, err.to_string()))?;
 
-            _ => panic!("Unexpected file format"),
+        if !in_map {
+            return Err(l.error(line, "map entry does not belong to any map"));
         }
+        entries.push((l.number, map_entry));
 
     }
+    finish_map(stages.last_mut(), &mut entries)?;
 
-    return almanac;
+    return Ok(Almanac { seeds, stages: chain_stages(stages)? });
 }
 
+/// Build the mapping of `stage` out of the `entries` read for it.
+fn finish_map(stage: Option<&mut Stage>, entries: &mut Vec<(usize, MapEntry)>) -> Result<()> {
+    let Some(stage) = stage else {
+        return Ok(());
+    };
+    if entries.is_empty() {
+        return Ok(());
+    }
 
-fn parse_seeds_line(s: &str) -> Vec<u64> {
-    (&s[7..]).split(" ")
-        .map(|x| x.parse::<u64>().expect("invalid seeds input!"))
-        .collect()    
+    let (lines, map_entries): (Vec<usize>, Vec<MapEntry>) = entries.drain(..).unzip();
+    stage.mapping = Mapping::sorted(map_entries).map_err(|(first, second)| {
+        Error::parse(lines[second], 1, format!("map entry overlaps the one on line {}", lines[first]))
+    })?;
+    Ok(())
 }
 
-fn lookup_helper(src: u64, mapping: &Mapping) -> u64 {
-    let results: Vec<u64> = mapping.iter()
-        .filter_map(|x| x.lookup(src))
+fn parse_map_header(line: Line) -> Result<Stage> {
+    let (from, to) = line.text.strip_suffix(" map:")
+        .and_then(|name| name.split_once("-to-"))
+        .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+        .ok_or_else(|| line.error(line.text, "expected a map header such as \"seed-to-soil map:\""))?;
+
+    Ok(Stage { from: from.to_string(), to: to.to_string(), mapping: Mapping::default(), line: line.number })
+}
+
+/// Put the stages in order, so that each starts where the previous one ends.
+/// They have to form a single chain: one category that nothing maps to, from
+/// which every stage can be reached.
+fn chain_stages(mut stages: Vec<Stage>) -> Result<Vec<Stage>> {
+    let header_error = |stage: &Stage, message: String| Error::parse(stage.line, 1, message);
+
+    let starts: Vec<usize> = (0..stages.len())
+        .filter(|&i| !stages.iter().any(|s| s.to == stages[i].from))
         .collect();
-    let answer = results.first().unwrap_or(&src);
-    return *answer;
+
+    let start = match starts.as_slice() {
+        [] if stages.is_empty() => return Ok(stages),
+        [] => return Err(header_error(&stages[0], "the maps form a loop, so there is no category to start from".to_string())),
+        [start] => *start,
+        [first, second, ..] => return Err(header_error(&stages[*second],
+            format!("map from {:?} is not connected to the maps starting from {:?}", stages[*second].from, stages[*first].from))),
+    };
+
+    let mut chain = vec!(stages.swap_remove(start));
+    while let Some(next) = stages.iter().position(|s| s.from == chain[chain.len() - 1].to) {
+        chain.push(stages.swap_remove(next));
+    }
+
+    // anything left over is in a loop of its own
+    if let Some(stage) = stages.iter().min_by_key(|s| s.line) {
+        return Err(header_error(stage, format!("map from {:?} is not connected to the maps starting from {:?}", stage.from, chain[0].from)));
+    }
+    return Ok(chain);
 }
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    let almanac = parse_input(input);
+fn parse_seeds_line(line: Line) -> Result<Vec<u64>> {
+    line.text["seeds:".len()..].split_whitespace()
+        .map(|x| line.parse::<u64>(x))
+        .collect()    
+}
 
-    almanac.seeds.iter()
-        .map(|x| almanac.map_seed_to_location(*x))
-        .min()
-        .unwrap()
-        .to_string()
+/// The parts of `ranges` that also lie in one of `others`.
+fn intersect_ranges(ranges: &[Range<u64>], others: &[Range<u64>]) -> Vec<Range<u64>> {
+    ranges.iter()
+        .flat_map(|a| others.iter().map(move |b| a.start.max(b.start)..a.end.min(b.end)))
+        .filter(|range| !range.is_empty())
+        .collect()
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
-    let almanac = parse_input(input);
+pub struct Day05;
 
-    let mut lowest: u64 = u64::MAX;
+impl Solver for Day05 {
+    type Input = Almanac;
 
-    // brute force. This is completely intractable for the size of the inputs.
-    for chunk in almanac.seeds.chunks_exact(2) {
-        let start = chunk[0];
-        let end = start + chunk[1] - 1;
+    fn day(&self) -> u32 {
+        5
+    }
 
-        for seed in start..=end {
-            let location = almanac.map_seed_to_location(seed);
-            lowest = lowest.min(location);
-        }
+    fn title(&self) -> &'static str {
+        "If You Give A Seed A Fertilizer"
+    }
+
+    fn parse(&self, input: &str) -> Result<Almanac> {
+        parse_input(input)
+    }
+
+    fn part1(&self, almanac: &Almanac) -> Result<String> {
+        solve_part1(almanac)
     }
 
-    return lowest.to_string();
+    fn part2(&self, almanac: &Almanac) -> Result<String> {
+        solve_part2(almanac)
+    }
 }
 
+pub(crate) fn solve_part1(almanac: &Almanac)  -> Result<String> {
+    let (first, last) = almanac.endpoints()?;
+    let mapping = almanac.composed(first, last)?;
+
+    almanac.seeds.iter()
+        .map(|x| mapping.lookup(*x))
+        .min()
+        .map(|x| x.to_string())
+        .ok_or_else(|| Error::solve("the almanac does not list any seeds"))
+}
+
+pub(crate) fn solve_part2(almanac: &Almanac)  -> Result<String> {
+    let seeds = almanac.seed_ranges()?;
+    let (first, last) = almanac.endpoints()?;
+
+    almanac.composed(first, last)?.lookup_ranges(seeds).iter()
+        .filter(|range| !range.is_empty())
+        .map(|range| range.start)
+        .min()
+        .map(|x| x.to_string())
+        .ok_or_else(|| Error::solve("the almanac does not list any seeds"))
+}
+
+// lists holding a single range of numbers are what these tests are about
 #[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_parse_seeds_line() {
-        let actual = parse_seeds_line("seeds: 7 154 211 43");
+        let actual = parse_seeds_line(Line::new(1, "seeds: 7 154 211 43")).unwrap();
         let expected: Vec<u64> = vec!(7, 154, 211, 43);
         assert_eq!(expected, actual);
     }
@@ -204,15 +571,221 @@ mod tests {
             range_len: 4,
         };
 
-        let mapping = vec!(me1, me2);
+        let mapping = Mapping::new(vec!(me2, me1)).unwrap();
 
         // source is not in any mapping
-        assert_eq!(1000, lookup_helper(1000, &mapping));
+        assert_eq!(1000, mapping.lookup(1000));
+        assert_eq!(4, mapping.lookup(4));
+        assert_eq!(0, mapping.lookup(0));
 
         // source is matched in me1
-        assert_eq!(11, lookup_helper(3, &mapping));
+        assert_eq!(11, mapping.lookup(3));
 
         // source is matched in me2
-        assert_eq!(203, lookup_helper(103, &mapping));
+        assert_eq!(203, mapping.lookup(103));
+    }
+
+    #[test]
+    fn test_map_entry_lookup_range() {
+        let map_entry = MapEntry{
+            src_range_start: 10,
+            dst_range_start: 100,
+            range_len: 5,
+        };
+
+        // range lies entirely outside the entry
+        assert_eq!((None, vec!(0..10)), map_entry.lookup_range(&(0..10)));
+        assert_eq!((None, vec!(15..20)), map_entry.lookup_range(&(15..20)));
+
+        // range lies entirely inside the entry
+        assert_eq!((Some(101..103), vec!()), map_entry.lookup_range(&(11..13)));
+
+        // range overlaps the entry on both sides
+        assert_eq!((Some(100..105), vec!(5..10, 15..20)), map_entry.lookup_range(&(5..20)));
+    }
+
+    #[test]
+    fn test_mapping_lookup_ranges() {
+        let mapping = Mapping::new(vec!(
+            MapEntry{ src_range_start: 98, dst_range_start: 50, range_len: 2 },
+            MapEntry{ src_range_start: 50, dst_range_start: 52, range_len: 48 },
+        )).unwrap();
+
+        let mut actual = mapping.lookup_ranges(vec!(79..93, 0..10, 95..101));
+        actual.sort_by_key(|r| r.start);
+        assert_eq!(vec!(0..10, 50..52, 81..95, 97..100, 100..101), actual);
+    }
+
+    #[test]
+    fn test_mapping_new() {
+        let mapping = Mapping::new(vec!(
+            MapEntry{ src_range_start: 20, dst_range_start: 0, range_len: 5 },
+            MapEntry{ src_range_start: 0, dst_range_start: 50, range_len: 10 },
+            MapEntry{ src_range_start: 10, dst_range_start: 70, range_len: 0 },
+            MapEntry{ src_range_start: 10, dst_range_start: 60, range_len: 5 },
+        )).unwrap();
+        let starts: Vec<u64> = mapping.entries.iter().map(|e| e.src_range_start).collect();
+        assert_eq!(vec!(0, 10, 20), starts);
+        assert_eq!(vec!(15..20), mapping.gaps());
+
+        let overlapping = vec!(
+            MapEntry{ src_range_start: 20, dst_range_start: 0, range_len: 5 },
+            MapEntry{ src_range_start: 0, dst_range_start: 50, range_len: 10 },
+            MapEntry{ src_range_start: 5, dst_range_start: 60, range_len: 10 },
+        );
+        assert_eq!((1, 2), Mapping::sorted(overlapping.clone()).unwrap_err());
+        let err = Mapping::new(overlapping).unwrap_err();
+        assert_eq!("the map entry for 5..15 overlaps the one for 0..10", err.to_string());
+    }
+
+    #[test]
+    fn test_map_entry_new() {
+        let mapping = Mapping::new(vec!(
+            MapEntry::new(50, 98, 2).unwrap(),
+            MapEntry::new(52, 50, 48).unwrap(),
+        )).unwrap();
+        assert_eq!(81, mapping.lookup(79));
+        assert!(mapping.gaps().is_empty());
+
+        let err = MapEntry::new(0, u64::MAX - 1, 2).unwrap_err();
+        assert_eq!("map entry runs past the largest possible number", err.to_string());
+        assert!(MapEntry::new(u64::MAX - 1, 0, 1).is_ok());
+    }
+
+    #[test]
+    fn test_mapping_compose() {
+        let first = Mapping::new(vec!(
+            MapEntry{ src_range_start: 98, dst_range_start: 50, range_len: 2 },
+            MapEntry{ src_range_start: 50, dst_range_start: 52, range_len: 48 },
+        )).unwrap();
+        let second = Mapping::new(vec!(
+            MapEntry{ src_range_start: 15, dst_range_start: 0, range_len: 37 },
+            MapEntry{ src_range_start: 52, dst_range_start: 37, range_len: 2 },
+            MapEntry{ src_range_start: 0, dst_range_start: 39, range_len: 15 },
+        )).unwrap();
+
+        let composed = first.compose(&second);
+        for x in 0..200 {
+            assert_eq!(second.lookup(first.lookup(x)), composed.lookup(x), "{}", x);
+        }
+        assert_eq!(u64::MAX - 1, composed.lookup(u64::MAX - 1));
+
+        // composing with an empty mapping changes nothing
+        let same = Mapping::default().compose(&first);
+        assert_eq!(first.entries, same.entries);
+    }
+
+    #[test]
+    fn test_solve_part2_odd_seeds() {
+        let almanac = parse_input("seeds: 79 14 55\n").unwrap();
+        assert!(solve_part2(&almanac).is_err());
+    }
+
+    const CUSTOM_CHAIN: &str = "seeds: 1 5 20
+
+water-to-light map:
+100 20 10
+
+seed-to-water map:
+20 0 10
+";
+
+    #[test]
+    fn test_chain_stages() {
+        // the sections are listed out of order, and use their own categories
+        let almanac = parse_input(CUSTOM_CHAIN).unwrap();
+        assert_eq!(vec!("seed", "water", "light"), almanac.categories());
+
+        assert_eq!(21, almanac.map("seed", "water", 1).unwrap());
+        assert_eq!(101, almanac.map("seed", "light", 1).unwrap());
+        assert_eq!(101, almanac.map("water", "light", 21).unwrap());
+        assert_eq!(5, almanac.map("seed", "seed", 5).unwrap());
+        assert!(almanac.map("light", "seed", 5).is_err());
+        assert!(almanac.map("seed", "soil", 5).is_err());
+
+        assert_eq!("100", solve_part1(&almanac).unwrap());
+    }
+
+    #[test]
+    fn test_chain_stages_errors() {
+        let err = parse_input("seeds: 1\n\na-to-b map:\n\nc-to-d map:\n").err().unwrap();
+        assert_eq!("line 5, column 1: map from \"c\" is not connected to the maps starting from \"a\"", err.to_string());
+
+        let err = parse_input("seeds: 1\n\na-to-b map:\n\nb-to-a map:\n").err().unwrap();
+        assert_eq!("line 3, column 1: the maps form a loop, so there is no category to start from", err.to_string());
+
+        let err = parse_input("seeds: 1\n\na-to-b map:\n\nc-to-d map:\n\nd-to-c map:\n").err().unwrap();
+        assert_eq!("line 5, column 1: map from \"c\" is not connected to the maps starting from \"a\"", err.to_string());
+
+        let err = parse_input("seeds: 1\n\na-to-b map:\n\na-to-c map:\n").err().unwrap();
+        assert_eq!("line 5, column 1: there is already a map from \"a\", on line 3", err.to_string());
+    }
+
+    #[test]
+    fn test_inverse() {
+        let almanac = parse_input(CUSTOM_CHAIN).unwrap();
+
+        // light 101 is reached from seed 1 through both maps, from seed 21
+        // through the second only, and from seed 101 through neither
+        assert_eq!(vec!(1, 21, 101), almanac.inverse("light", "seed", 101).unwrap());
+        assert_eq!(vec!(0, 20), almanac.inverse("water", "seed", 20).unwrap());
+        assert_eq!(vec!(21, 101), almanac.inverse("light", "water", 101).unwrap());
+        assert!(almanac.inverse("seed", "light", 1).is_err());
+
+        // every seed found maps back to the location it was found from
+        for seed in almanac.inverse("light", "seed", 105).unwrap() {
+            assert_eq!(105, almanac.map("seed", "light", seed).unwrap());
+        }
+
+        // seeds reaching lights below 103
+        let mut seeds = almanac.inverse_ranges("light", "seed", vec!(0..103)).unwrap();
+        seeds.sort_by_key(|r| r.start);
+        assert_eq!(vec!(0..3, 10..20, 20..23, 30..103), seeds);
+    }
+
+    #[test]
+    fn test_lowest_reachable() {
+        let almanac = parse_input(CUSTOM_CHAIN).unwrap();
+        assert_eq!(Some(10), almanac.lowest_reachable(&[0..40]).unwrap());
+        assert_eq!(Some(35), almanac.lowest_reachable(&[35..40]).unwrap());
+        assert_eq!(None, almanac.lowest_reachable(&[]).unwrap());
+
+        // cross-check part 2 from the location side
+        let almanac = parse_input("seeds: 5 10 100 3\n\nseed-to-soil map:\n0 100 2\n50 0 20\n").unwrap();
+        let lowest = almanac.lowest_reachable(&almanac.seed_ranges().unwrap()).unwrap();
+        assert_eq!(solve_part2(&almanac).unwrap(), lowest.unwrap().to_string());
+    }
+
+    #[test]
+    fn test_seed_range_overflow() {
+        let almanac = parse_input("seeds: 18446744073709551615 2
+
+seed-to-soil map:
+50 98 2
+").unwrap();
+        assert_eq!("2", solve_part1(&almanac).unwrap());
+        let err = solve_part2(&almanac).unwrap_err();
+        assert_eq!("the seed range starting at 18446744073709551615 runs past the largest possible number", err.to_string());
+    }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input("seeds: 79 14\n\nseed-to-soil map:\n50 98\n").err().unwrap();
+        assert_eq!("line 4, column 1: expected a map entry of 3 numbers", err.to_string());
+
+        let err = parse_input("seeds: 79 14\n\nseed to soil map:\n50 98 2\n").err().unwrap();
+        assert_eq!("line 3, column 1: expected a map header such as \"seed-to-soil map:\"", err.to_string());
+
+        let err = parse_input("seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n0 90 10\n").err().unwrap();
+        assert_eq!("line 5, column 1: map entry overlaps the one on line 4", err.to_string());
+
+        let err = parse_input("seeds: 79 14\n\nseed-to-soil map:\n50 18446744073709551610 10\n").err().unwrap();
+        assert_eq!("line 4, column 1: map entry runs past the largest possible number", err.to_string());
+
+        let err = parse_input("seeds: 79 14\n50 98 2\n").err().unwrap();
+        assert_eq!("line 2, column 1: map entry does not belong to any map", err.to_string());
+
+        let err = parse_input("seeds: 79 1x4\n").err().unwrap();
+        assert_eq!("line 1, column 11: expected a number, found \"1x4\"", err.to_string());
     }
 }
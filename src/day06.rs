@@ -1,22 +1,77 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
 
-use regex::Regex;
+use crate::error::{ self, Error, Line, Result };
+use crate::solver::Solver;
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    let mut times: Vec<u64> = vec!();
-    let mut distances: Vec<u64> = vec!();
+pub struct Day06;
+
+#[derive(Debug)]
+pub struct Races {
+    times: Vec<u64>,
+    distances: Vec<u64>,
+    /// The digits of each line as written, which part 2 reads as one number.
+    /// Joining the parsed numbers instead would lose any leading zeros.
+    time_digits: String,
+    distance_digits: String,
+}
 
-    for line in input {
-        let line = line.unwrap();
+impl Solver for Day06 {
+    type Input = Races;
+
+    fn day(&self) -> u32 {
+        6
+    }
+
+    fn title(&self) -> &'static str {
+        "Wait For It"
+    }
+
+    fn parse(&self, input: &str) -> Result<Races> {
+        parse_input(input)
+    }
+
+    fn part1(&self, races: &Races) -> Result<String> {
+        Ok(solve_part1(&races.times, &races.distances))
+    }
+
+    fn part2(&self, races: &Races) -> Result<String> {
+        solve_part2(&races.time_digits, &races.distance_digits)
+    }
+}
 
-        if line.contains("Time:") {
-            times = to_vec(&line[5..].trim());
-        } else if line.contains("Distance:") {
-            distances = to_vec(&line[9..].trim())
+fn parse_input(input: &str) -> Result<Races> {
+    let mut times: Vec<u64> = vec!();
+    let mut distances: Vec<u64> = vec!();
+    let mut time_digits = String::new();
+    let mut distance_digits = String::new();
+    let mut last_line = 0;
+
+    for line in error::lines(input) {
+
+        if let Some(rest) = line.text.strip_prefix("Time:") {
+            times = to_vec(rest, line)?;
+            time_digits = rest.split_whitespace().collect();
+        } else if let Some(rest) = line.text.strip_prefix("Distance:") {
+            distances = to_vec(rest, line)?;
+            distance_digits = rest.split_whitespace().collect();
         }
+        last_line = line.number;
+    }
+
+    let last_line = last_line.max(1);
+    if times.is_empty() {
+        return Err(Error::parse(last_line, 1, "expected a Time: line listing the race times"));
     }
+    if distances.is_empty() {
+        return Err(Error::parse(last_line, 1, "expected a Distance: line listing the record distances"));
+    }
+    if times.len() != distances.len() {
+        return Err(Error::parse(last_line, 1, format!("found {} race times but {} record distances", times.len(), distances.len())));
+    }
+
+    Ok(Races { times, distances, time_digits, distance_digits })
+}
 
+pub(crate) fn solve_part1(times: &[u64], distances: &[u64])  -> String {
     let mut ttl = 1;
 
     for (i, time) in times.iter().enumerate() {
@@ -26,63 +81,87 @@ pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
     return ttl.to_string();
 }
 
+/// Count the button-hold times `p` that beat the record, i.e. for which
+/// `p * (race_time - p) > record_distance`.
+///
+/// The distance is a parabola that is symmetric around `race_time / 2`, so the
+/// winning hold times are the whole numbers strictly between its roots
+/// `(race_time ± √(race_time² - 4 · record_distance)) / 2`. The integer square
+/// root gets us within one of the lower bound, which is then settled exactly.
+/// All of this is done in u128, which cannot overflow for u64 inputs.
 fn count_ways_to_win(race_time: u64, record_distance: u64) -> u64 {
-    let mut count = 0;
-
-    // p = amount of time to hold the button down
-    for p in 1..race_time {
-        // This function is a symmetric curve, so we should be optimize 
-        // (maybe even find a closed form solution).
-        // For example, start from the midway point, find how many f(p)'s are
-        // above the threshold, then multiply by two.
-        // Alternatively, count from the beginning and find p_1 where f(p) first
-        // crosses above the record distance. The last winning p (p_2) should be 
-        // at something like (race_time - p_1).
-        // Then the distance between p_2 and p_1 should be the number
-        // of ways in which the race can be won. 
-        let d = p * (race_time - p);
-        if d > record_distance {
-            count += 1;
-        }
+    let (t, d) = (race_time as u128, record_distance as u128);
+    let beats_record = |p: u128| p * (t - p) > d;
+
+    let half = t / 2;
+    if !beats_record(half) {
+        return 0;
     }
 
-    count
+    // beating the record at `half` means t² > 4d
+    let root = (t * t - 4 * d).isqrt();
+    let mut first = ((t - root) / 2).min(half);
+    while first > 0 && beats_record(first - 1) {
+        first -= 1;
+    }
+    while !beats_record(first) {
+        first += 1;
+    }
+
+    // the last winning hold time mirrors the first one
+    return (t - 2 * first + 1) as u64;
 }
 
-fn to_vec(line: &str) -> Vec<u64> {
-    Regex::new("\\s+").unwrap()
-        .split(line)
-        .map(|x| x.parse::<u64>().unwrap())
+fn to_vec(numbers: &str, line: Line) -> Result<Vec<u64>> {
+    numbers.split_whitespace()
+        .map(|x| line.parse::<u64>(x))
         .collect()
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
-    let mut time = 0;
-    let mut distance = 0;
-
-    for line in input {
-        let line = line.unwrap();
-
-        if line.contains("Time:") {
-            time = concat(&line[5..].trim());
-        } else if line.contains("Distance:") {
-            distance = concat(&line[9..].trim())
-        }
-    }
+pub(crate) fn solve_part2(time_digits: &str, distance_digits: &str)  -> Result<String> {
+    // In part 2 there is only one race; the spaces between the numbers
+    // were just bad kerning.
+    let time = concat(time_digits)?;
+    let distance = concat(distance_digits)?;
 
     let x = count_ways_to_win(time, distance);
 
-    return x.to_string();
+    return Ok(x.to_string());
 }
 
-fn concat(line: &str) -> u64 {
-    line.replace(" ", "").parse::<u64>().unwrap()
+fn concat(digits: &str) -> Result<u64> {
+    digits.parse::<u64>()
+        .map_err(|_| Error::solve(format!("{} is not a valid race number", digits)))
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
+    fn count_ways_to_win_by_trying(race_time: u64, record_distance: u64) -> u64 {
+        (1..race_time)
+            .filter(|p| p * (race_time - p) > record_distance)
+            .count() as u64
+    }
+
+    /// Check the count against the hold times either side of where winning
+    /// starts, for races too long to try every hold time.
+    fn check_boundaries(race_time: u64, record_distance: u64) {
+        let (t, d) = (race_time as u128, record_distance as u128);
+        let count = count_ways_to_win(race_time, record_distance) as u128;
+        if count == 0 {
+            assert!((t / 2) * (t - t / 2) <= d);
+            return;
+        }
+
+        let first = (t + 1 - count) / 2;
+        assert_eq!(t, 2 * first + count - 1, "the winning hold times are not symmetric");
+        assert!(first * (t - first) > d);
+        assert!((first - 1) * (t - first + 1) <= d);
+    }
+
     #[test]
     fn test_count_ways_to_win() {
         let times = vec!(7, 15, 30, 145);
@@ -97,4 +176,62 @@ mod tests {
             assert_eq!(e, count_ways_to_win(t, d));
         }
     }
+
+    #[test]
+    fn test_count_ways_to_win_near_limits() {
+        let max = u64::MAX;
+        for (t, d) in [(max, 0), (max, max), (max, max - 1), (max - 1, max), (2, 0), (1, 0), (0, 0)] {
+            check_boundaries(t, d);
+        }
+        assert_eq!(max - 1, count_ways_to_win(max, 0));
+        assert_eq!(1, count_ways_to_win(2, 0));
+        assert_eq!(0, count_ways_to_win(0, 0));
+    }
+
+    proptest! {
+        #[test]
+        fn test_count_ways_to_win_matches_trying(race_time in 0u64..2000, record_distance in 0u64..1_100_000) {
+            prop_assert_eq!(count_ways_to_win_by_trying(race_time, record_distance), count_ways_to_win(race_time, record_distance));
+        }
+
+        #[test]
+        fn test_count_ways_to_win_boundaries(race_time: u64, record_distance: u64) {
+            check_boundaries(race_time, record_distance);
+        }
+
+        #[test]
+        fn test_count_ways_to_win_close_races(race_time in 1u64..(1 << 33), slack in 0u64..1000) {
+            // records just below the best possible distance, which still
+            // fits in a u64 for races this long
+            let best = (race_time / 2) * (race_time - race_time / 2);
+            check_boundaries(race_time, best.saturating_sub(slack));
+        }
+    }
+
+    #[test]
+    fn test_part_2_keeps_leading_zeros() {
+        let races = parse_input("Time:      7  05\nDistance:  9  040\n").unwrap();
+        assert_eq!(vec!(7, 5), races.times);
+        assert_eq!("705", races.time_digits);
+        assert_eq!("9040", races.distance_digits);
+        assert_eq!(count_ways_to_win(705, 9040).to_string(), solve_part2(&races.time_digits, &races.distance_digits).unwrap());
+    }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input("Time:      7  15   30\nDistance:  9  40\n").unwrap_err();
+        assert_eq!("line 2, column 1: found 3 race times but 2 record distances", err.to_string());
+
+        let err = parse_input("").unwrap_err();
+        assert_eq!("line 1, column 1: expected a Time: line listing the race times", err.to_string());
+
+        let err = parse_input("Time:\nDistance:  9  40\n").unwrap_err();
+        assert_eq!("line 2, column 1: expected a Time: line listing the race times", err.to_string());
+
+        let err = parse_input("Time:      7  15\n").unwrap_err();
+        assert_eq!("line 1, column 1: expected a Distance: line listing the record distances", err.to_string());
+
+        let err = parse_input("Time:      7  1five   30\n").unwrap_err();
+        assert_eq!("line 1, column 15: expected a number, found \"1five\"", err.to_string());
+    }
 }
@@ -1,37 +1,201 @@
 use std::cmp::Ordering;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-
-const A_VAL: u32 = 14;
-const K_VAL: u32 = 13;
-const Q_VAL: u32 = 12;
-const J_VAL: u32 = 11;
-const T_VAL: u32 = 10;
-
-type HandCards = [u32; 5];
-
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
-enum HandType {
-    HighCard,
-    OnePair,
-    TwoPair,
-    ThreeOfAKind,
-    FullHouse,
-    FourOfAKind,
-    FiveOfAKind,
+use std::fmt;
+
+use crate::error::{ self, Error, Result };
+use crate::runner::{ csv_field, Format };
+use crate::solver::Solver;
+
+const VALID_CARDS: &str = "AKQJT98765432";
+
+type HandCards = Vec<u32>;
+
+/// A kind of hand, such as a full house, and how to recognise it.
+#[derive(Clone)]
+pub struct HandType {
+    pub name: &'static str,
+    pattern: Pattern,
+}
+
+#[derive(Clone)]
+enum Pattern {
+    /// Groups of equal cards at least this big, largest first, so that
+    /// `[3, 2]` is a full house and `[]` is any hand at all.
+    Groups(Vec<usize>),
+    /// Decided from the strength of each card, in the order they were dealt.
+    Custom(fn(&[u32]) -> bool),
+}
+
+impl HandType {
+    pub fn groups(name: &'static str, sizes: &[usize]) -> Self {
+        let mut sizes = sizes.to_vec();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        HandType { name, pattern: Pattern::Groups(sizes) }
+    }
+
+    pub fn custom(name: &'static str, matches: fn(&[u32]) -> bool) -> Self {
+        HandType { name, pattern: Pattern::Custom(matches) }
+    }
+
+    /// The hand types from the puzzle, strongest first.
+    pub fn puzzle() -> Vec<HandType> {
+        vec!(
+            HandType::groups("five of a kind", &[5]),
+            HandType::groups("four of a kind", &[4]),
+            HandType::groups("full house", &[3, 2]),
+            HandType::groups("three of a kind", &[3]),
+            HandType::groups("two pair", &[2, 2]),
+            HandType::groups("one pair", &[2]),
+            HandType::groups("high card", &[]),
+        )
+    }
+
+    fn matches(&self, cards: &[u32], groups: &[usize]) -> bool {
+        match &self.pattern {
+            Pattern::Groups(sizes) => sizes.len() <= groups.len()
+                && sizes.iter().zip(groups).all(|(size, group)| group >= size),
+            Pattern::Custom(matches) => matches(cards),
+        }
+    }
+}
+
+/// The sizes of the groups of equal cards in a hand, largest first.
+fn group_sizes(cards: &[u32]) -> Vec<usize> {
+    let mut sorted = cards.to_vec();
+    sorted.sort_unstable();
+    let mut groups: Vec<usize> = sorted.chunk_by(|a, b| a == b)
+        .map(|group| group.len())
+        .collect();
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+    groups
 }
 
 #[derive(Eq, Debug)]
-struct Hand {
+pub struct Hand {
+    /// The strength of each card, as dealt, for breaking ties between hands
+    /// of the same type.
     cards: HandCards,
-    typ: HandType,
+    /// How strong the type of the hand is, with the weakest type at 0.
+    strength: usize,
+    type_name: &'static str,
+    /// The card that the wildcards stood in for, if there were any.
+    substitute: Option<char>,
+}
+
+impl Hand {
+    pub fn hand_type(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub fn substitute(&self) -> Option<char> {
+        self.substitute
+    }
+}
+
+/// How a game of Camel Cards is played: the cards from weakest to strongest,
+/// which of them are wild, how many cards make a hand, and the types of hand
+/// from strongest to weakest.
+///
+/// Wildcards all stand in for the same card, whichever makes the best type
+/// of hand, but keep their own strength when breaking ties.
+#[derive(Clone)]
+pub struct CardRules {
+    order: Vec<char>,
+    wildcards: Vec<char>,
+    hand_size: usize,
+    types: Vec<HandType>,
+}
+
+impl CardRules {
+    /// Rules with the cards in `order`, weakest first, and the hands and
+    /// hand types from the puzzle.
+    pub fn new(order: &str, wildcards: &str) -> Self {
+        CardRules {
+            order: order.chars().collect(),
+            wildcards: wildcards.chars().collect(),
+            hand_size: 5,
+            types: HandType::puzzle(),
+        }
+    }
+
+    /// The rules for part 1.
+    pub fn standard() -> Self {
+        CardRules::new("23456789TJQKA", "")
+    }
+
+    /// The rules for part 2, where J is a joker and the weakest card.
+    pub fn jokers() -> Self {
+        CardRules::new("J23456789TQKA", "J")
+    }
+
+    pub fn with_hand_size(self, hand_size: usize) -> Self {
+        CardRules { hand_size, ..self }
+    }
+
+    /// Use `types`, strongest first, instead of the ones from the puzzle. A
+    /// hand is the first type it matches, so the last type should usually
+    /// match any hand.
+    pub fn with_types(self, types: Vec<HandType>) -> Self {
+        CardRules { types, ..self }
+    }
+
+    fn strength(&self, card: char) -> Option<u32> {
+        self.order.iter().position(|&c| c == card).map(|i| i as u32)
+    }
+
+    fn is_wild(&self, card: char) -> bool {
+        self.wildcards.contains(&card)
+    }
+
+    /// The index in `types` of the first type that `cards` make.
+    fn classify(&self, cards: &[u32]) -> Option<usize> {
+        let groups = group_sizes(cards);
+        self.types.iter().position(|typ| typ.matches(cards, &groups))
+    }
+
+    pub fn hand(&self, text: &str) -> Result<Hand> {
+        if text.chars().count() != self.hand_size {
+            return Err(Error::solve(format!("{} does not have exactly {} cards", text, self.hand_size)));
+        }
+        let cards: HandCards = text.chars()
+            .map(|c| self.strength(c)
+                .ok_or_else(|| Error::solve(format!("{} is not one of the cards {}", c, String::from_iter(&self.order)))))
+            .collect::<Result<_>>()?;
+
+        // try the wildcards as each of the other cards, preferring the
+        // strongest card when several make equally good hands
+        let mut substitutes = self.order.iter().rev().filter(|&&c| !self.is_wild(c)).peekable();
+        let mut best = None;
+        if text.chars().any(|c| self.is_wild(c)) && substitutes.peek().is_some() {
+            for &substitute in substitutes {
+                let played: Vec<u32> = cards.iter().zip(text.chars())
+                    .map(|(&card, c)| if self.is_wild(c) { self.strength(substitute).unwrap() } else { card })
+                    .collect();
+                if let Some(typ) = self.classify(&played) {
+                    if best.is_none_or(|(best_typ, _)| typ < best_typ) {
+                        best = Some((typ, Some(substitute)));
+                    }
+                }
+            }
+        } else {
+            best = self.classify(&cards).map(|typ| (typ, None));
+        }
+
+        let (typ, substitute) = best
+            .ok_or_else(|| Error::solve(format!("{} is not any of the types of hand", text)))?;
+        Ok(Hand {
+            cards,
+            strength: self.types.len() - 1 - typ,
+            type_name: self.types[typ].name,
+            substitute,
+        })
+    }
 }
 
 impl Ord for Hand {
     fn cmp(&self, other: &Self) -> Ordering {
-        if self.typ < other.typ {
+        if self.strength < other.strength {
             return Ordering::Less;
-        } else if self.typ > other.typ {
+        } else if self.strength > other.strength {
             return Ordering::Greater;
         }
 
@@ -56,168 +220,159 @@ impl PartialEq for Hand {
     }
 }
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    let mut data = parse_input_part_1(input);
-    data.sort();
+pub struct Day07;
+
+impl Solver for Day07 {
+    type Input = Vec<(String, u32)>;
+
+    fn day(&self) -> u32 {
+        7
+    }
 
+    fn title(&self) -> &'static str {
+        "Camel Cards"
+    }
 
-    let mut total_winnings = 0;
+    fn parse(&self, input: &str) -> Result<Vec<(String, u32)>> {
+        parse_input(input)
+    }
 
-    for (i, (_, bid)) in data.iter().enumerate() {
-        let rank = i as u32 + 1;
-        let winnings = rank * bid;
-        total_winnings += winnings;
+    fn part1(&self, hands: &Vec<(String, u32)>) -> Result<String> {
+        solve_part1(hands)
     }
 
-    return total_winnings.to_string();
+    fn part2(&self, hands: &Vec<(String, u32)>) -> Result<String> {
+        solve_part2(hands)
+    }
+
+    fn explain(&self, hands: &Vec<(String, u32)>, format: Format) -> Option<String> {
+        explain_tournaments(hands, format)
+    }
 }
 
-fn parse_input_part_1(input: Lines<BufReader<File>>) -> Vec<(Hand, u32)> {
+fn parse_input(input: &str) -> Result<Vec<(String, u32)>> {
     let mut hands = vec!();
 
-    for line in input {
-        let line = line.unwrap();
-        let hand = convert_str_to_hand_part_1(&line[..5]);
-        let bid = (&line[6..]).parse::<u32>().unwrap();
+    for line in error::lines(input) {
+        let (hand, bid) = line.text.split_once(" ")
+            .ok_or_else(|| line.error(line.text, "expected a hand and a bid"))?;
 
-        hands.push((hand, bid));
+        if hand.chars().count() != 5 {
+            return Err(line.error(hand, "a hand must have exactly 5 cards"));
+        }
+        if let Some(i) = hand.find(|c| !VALID_CARDS.contains(c)) {
+            return Err(line.error(&hand[i..], "not a valid card"));
+        }
+        let bid = line.parse::<u32>(bid)?;
+
+        hands.push((hand.to_string(), bid));
     }
-    hands
+    Ok(hands)
 }
 
-fn convert_str_to_hand_part_1(s: &str) -> Hand {
-    let mut cards: HandCards = [0; 5];
-    let mut counts = [0; 15];
-
-    let mut chars = s.chars();
-
-    for i in 0..5 {
-        let c = chars.next().unwrap();
-        let v = match c {
-            'A' => A_VAL,
-            'K' => K_VAL,
-            'Q' => Q_VAL,
-            'J' => J_VAL,
-            'T' => T_VAL,
-            '2'..='9' => c.to_digit(10).unwrap(),
-            _ => panic!("Not a valid card!"),
-        };
-        counts[v as usize] += 1;
-        cards[i] = v;
-    }
-
-    let mut counts: Vec<i32> = counts.iter().map(|x| *x).filter(|&x| x>1).collect();
-    counts.sort();
-
-    let typ ;
-
-    if counts.is_empty() {
-        typ = HandType::HighCard;
-    } else if counts == vec!(2) {
-        typ = HandType::OnePair;
-    } else if counts == vec!(2, 2) {
-        typ = HandType::TwoPair;
-    } else if counts == vec!(3) {
-        typ = HandType::ThreeOfAKind;
-    } else if counts == vec!(2, 3) {
-        typ = HandType::FullHouse;
-    } else if counts == vec!(4) {
-        typ = HandType::FourOfAKind;
-    } else if counts == vec!(5) {
-        typ = HandType::FiveOfAKind
-    } else {
-        panic!("Unexpected card type: {:?}", counts);
-    }
-
-    Hand{ cards, typ }
+pub(crate) fn solve_part1(hands: &[(String, u32)])  -> Result<String> {
+    total_winnings(hands, &CardRules::standard())
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
-    let mut data = parse_input_part_2(input);
-    data.sort();
-
+pub(crate) fn solve_part2(hands: &[(String, u32)])  -> Result<String> {
+    total_winnings(hands, &CardRules::jokers())
+}
 
-    let mut total_winnings = 0;
+fn total_winnings(hands: &[(String, u32)], rules: &CardRules) -> Result<String> {
+    Ok(Tournament::new(hands, rules)?.total_winnings().to_string())
+}
 
-    for (i, (_, bid)) in data.iter().enumerate() {
-        let rank = i as u32 + 1;
-        let winnings = rank * bid;
-        total_winnings += winnings;
-    }
+/// Where one hand finished in a tournament, and what it won.
+#[derive(Debug, PartialEq)]
+pub struct Ranked {
+    pub cards: String,
+    pub hand_type: &'static str,
+    /// The card that the wildcards stood in for, if there were any.
+    pub substitute: Option<char>,
+    pub bid: u32,
+    /// The rank of the hand, with 1 for the weakest.
+    pub rank: u32,
+    pub winnings: u32,
+}
 
-    return total_winnings.to_string();
+/// Every hand ranked under some rules, weakest first.
+pub struct Tournament {
+    pub hands: Vec<Ranked>,
 }
 
-fn parse_input_part_2(input: Lines<BufReader<File>>) -> Vec<(Hand, u32)> {
-    let mut hands = vec!();
+impl Tournament {
+    pub fn new(hands: &[(String, u32)], rules: &CardRules) -> Result<Self> {
+        let mut data: Vec<(Hand, &str, u32)> = hands.iter()
+            .map(|(s, bid)| Ok((rules.hand(s)?, s.as_str(), *bid)))
+            .collect::<Result<_>>()?;
+        data.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let hands = data.into_iter().enumerate().map(|(i, (hand, cards, bid))| {
+            let rank = i as u32 + 1;
+            Ranked {
+                cards: cards.to_string(),
+                hand_type: hand.hand_type(),
+                substitute: hand.substitute(),
+                bid,
+                rank,
+                winnings: rank * bid,
+            }
+        }).collect();
+
+        Ok(Tournament { hands })
+    }
 
-    for line in input {
-        let line = line.unwrap();
-        let hand = convert_str_to_hand_part_2(&line[..5]);
-        let bid = (&line[6..]).parse::<u32>().unwrap();
+    pub fn total_winnings(&self) -> u32 {
+        self.hands.iter().map(|hand| hand.winnings).sum()
+    }
 
-        hands.push((hand, bid));
+    /// The hands as CSV rows, after a header, each starting with `part`.
+    pub fn to_csv(&self, part: u32) -> String {
+        let mut csv = String::new();
+        for hand in &self.hands {
+            let substitute = hand.substitute.map(String::from).unwrap_or_default();
+            csv.push_str(&format!("{},{},{},{},{},{},{}\n",
+                part, hand.rank, csv_field(&hand.cards), csv_field(hand.hand_type), substitute, hand.bid, hand.winnings));
+        }
+        csv
     }
-    hands
 }
 
-fn convert_str_to_hand_part_2(s: &str) -> Hand {
-    let mut cards: HandCards = [0; 5];
-    let mut counts = [0; 15];
-
-    let mut chars = s.chars();
-
-    for i in 0..5 {
-        let c = chars.next().unwrap();
-        let v = match c {
-            'A' => A_VAL,
-            'K' => K_VAL,
-            'Q' => Q_VAL,
-            'J' => 0, // In part 2, J is now the weakest individual card
-            'T' => T_VAL,
-            '2'..='9' => c.to_digit(10).unwrap(),
-            _ => panic!("Not a valid card!"),
-        };
-        counts[v as usize] += 1;
-        cards[i] = v;
-    }
-
-    let joker_count = counts[0];
-
-    let mut counts: Vec<i32> = counts[1..].iter().map(|x| *x).filter(|&x| x>1).collect();
-    counts.sort();
-    if joker_count > 0 {
-        if counts.len() > 0 {
-            let last_index = counts.len() - 1;
-            counts[last_index] += joker_count;
-        } else if joker_count < 5 {
-            counts = vec!(joker_count + 1);
-        } else {
-            counts = vec!(5);
+const CSV_HEADER: &str = "part,rank,hand,type,wildcards_as,bid,winnings\n";
+
+impl fmt::Display for Tournament {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.hands.iter().map(|hand| hand.cards.len()).max().unwrap_or(0).max(4);
+        let type_width = self.hands.iter().map(|hand| hand.hand_type.len()).max().unwrap_or(0).max(4);
+        writeln!(f, "{:>5}  {:<width$}  {:<type_width$}  {:<12}  {:>6}  {:>9}", "Rank", "Hand", "Type", "Wildcards as", "Bid", "Winnings")?;
+        for hand in &self.hands {
+            let substitute = hand.substitute.map_or("-".to_string(), String::from);
+            writeln!(f, "{:>5}  {:<width$}  {:<type_width$}  {:<12}  {:>6}  {:>9}",
+                hand.rank, hand.cards, hand.hand_type, substitute, hand.bid, hand.winnings)?;
         }
+        write!(f, "Total winnings: {}", self.total_winnings())
     }
+}
 
-    let typ ;
-
-    if counts.is_empty() {
-        typ = HandType::HighCard;
-    } else if counts == vec!(2) {
-        typ = HandType::OnePair;
-    } else if counts == vec!(2, 2) {
-        typ = HandType::TwoPair;
-    } else if counts == vec!(3) {
-        typ = HandType::ThreeOfAKind;
-    } else if counts == vec!(2, 3) {
-        typ = HandType::FullHouse;
-    } else if counts == vec!(4) {
-        typ = HandType::FourOfAKind;
-    } else if counts == vec!(5) {
-        typ = HandType::FiveOfAKind
-    } else {
-        panic!("Unexpected card type: {:?}", counts);
+/// Both parts' tournaments, as a table for each or as one CSV document.
+fn explain_tournaments(hands: &[(String, u32)], format: Format) -> Option<String> {
+    let tournaments = [CardRules::standard(), CardRules::jokers()].iter()
+        .map(|rules| Tournament::new(hands, rules))
+        .collect::<Result<Vec<_>>>();
+    let tournaments = match tournaments {
+        Ok(tournaments) => tournaments,
+        Err(err) => return Some(format!("{}\n", err)),
+    };
+
+    match format {
+        Format::Text => Some(tournaments.iter().enumerate()
+            .map(|(i, tournament)| format!("Part {}\n{}\n", i + 1, tournament))
+            .collect::<Vec<_>>()
+            .join("\n")),
+        Format::Csv => Some(tournaments.iter().enumerate()
+            .fold(CSV_HEADER.to_string(), |csv, (i, tournament)| csv + &tournament.to_csv(i as u32 + 1))),
+        Format::Json => None,
     }
-
-    Hand{ cards, typ }
 }
 
 
@@ -225,22 +380,26 @@ fn convert_str_to_hand_part_2(s: &str) -> Hand {
 mod tests {
     use super::*;
 
+    fn convert_str_to_hand_part_1(s: &str) -> Hand {
+        CardRules::standard().hand(s).unwrap()
+    }
+
     #[test]
     fn test_decide_hand_type() {
         let hands = vec!(
-            ("A2345", HandType::HighCard),
-            ("AA234", HandType::OnePair),
-            ("23445", HandType::OnePair),
-            ("2T42T", HandType::TwoPair),
-            ("23433", HandType::ThreeOfAKind),
-            ("KQKQQ", HandType::FullHouse),
-            ("JJJ4J", HandType::FourOfAKind),
-            ("AAAAA", HandType::FiveOfAKind),
+            ("A2345", "high card"),
+            ("AA234", "one pair"),
+            ("23445", "one pair"),
+            ("2T42T", "two pair"),
+            ("23433", "three of a kind"),
+            ("KQKQQ", "full house"),
+            ("JJJ4J", "four of a kind"),
+            ("AAAAA", "five of a kind"),
         );
 
         for (txt, expected) in hands {
             let hand = convert_str_to_hand_part_1(txt);
-            assert_eq!(expected, hand.typ, "Expected {:?} for hand: {}, got: {:?}", expected, txt, hand.typ);
+            assert_eq!(expected, hand.hand_type(), "Expected {:?} for hand: {}, got: {:?}", expected, txt, hand.hand_type());
         }
     }
 
@@ -258,12 +417,13 @@ mod tests {
 
     #[test]
     fn test_compare_hand_types() {
-        assert!(HandType::FiveOfAKind > HandType::FourOfAKind);
-        assert!(HandType::FourOfAKind > HandType::FullHouse);
-        assert!(HandType::FullHouse > HandType::ThreeOfAKind);
-        assert!(HandType::ThreeOfAKind > HandType::TwoPair);
-        assert!(HandType::TwoPair > HandType::OnePair);
-        assert!(HandType::OnePair > HandType::HighCard);
+        let strength = |cards| convert_str_to_hand_part_1(cards).strength;
+        assert!(strength("22222") > strength("AAAA2"));
+        assert!(strength("AAAA2") > strength("22233"));
+        assert!(strength("22233") > strength("AAA23"));
+        assert!(strength("AAA23") > strength("AAKK2"));
+        assert!(strength("AAKK2") > strength("AAKQ2"));
+        assert!(strength("AAKQ2") > strength("AKQJ2"));
     }
 
     #[test]
@@ -288,4 +448,137 @@ mod tests {
         assert_eq!(hand6, hand7);
     }
 
+    #[test]
+    fn test_jokers() {
+        let rules = CardRules::jokers();
+        let hands = vec!(
+            ("KTJJT", "four of a kind", Some('T')),
+            ("QQQJA", "four of a kind", Some('Q')),
+            ("2345J", "one pair", Some('5')),
+            ("JJJJJ", "five of a kind", Some('A')),
+            ("T55J5", "four of a kind", Some('5')),
+            ("32T3K", "one pair", None),
+        );
+
+        for (txt, expected, substitute) in hands {
+            let hand = rules.hand(txt).unwrap();
+            assert_eq!(expected, hand.hand_type(), "Expected {:?} for hand: {}, got: {:?}", expected, txt, hand.hand_type());
+            assert_eq!(substitute, hand.substitute(), "for hand: {}", txt);
+        }
+
+        // jokers are the weakest card when breaking ties
+        assert!(rules.hand("JKKK2").unwrap() < rules.hand("QQQQ2").unwrap());
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        // several wildcards, and aces low
+        let rules = CardRules::new("A23456789TJQK", "27");
+        let hand = rules.hand("A2K7K").unwrap();
+        assert_eq!("four of a kind", hand.hand_type());
+        assert_eq!(Some('K'), hand.substitute);
+        assert!(hand < rules.hand("2A2AA").unwrap());
+        assert!(rules.hand("AAAKK").unwrap() < rules.hand("KKKAA").unwrap());
+
+        // a simpler game where only pairs count
+        let rules = CardRules::standard().with_types(vec!(
+            HandType::groups("pair", &[2]),
+            HandType::groups("nothing", &[]),
+        ));
+        assert_eq!("pair", rules.hand("AAAAA").unwrap().hand_type());
+        assert!(rules.hand("2233A").unwrap() < rules.hand("22345").unwrap());
+
+        let err = CardRules::standard().hand("AAAA1").err().unwrap();
+        assert_eq!("1 is not one of the cards 23456789TJQKA", err.to_string());
+        let err = CardRules::standard().hand("AAAA").err().unwrap();
+        assert_eq!("AAAA does not have exactly 5 cards", err.to_string());
+    }
+
+    fn is_straight(cards: &[u32]) -> bool {
+        let mut sorted = cards.to_vec();
+        sorted.sort_unstable();
+        sorted.windows(2).all(|pair| pair[1] == pair[0] + 1)
+    }
+
+    #[test]
+    fn test_six_card_hands() {
+        let rules = CardRules::new("23456789TJQKA", "J")
+            .with_hand_size(6)
+            .with_types(vec!(
+                HandType::groups("two three of a kinds", &[3, 3]),
+                HandType::custom("straight", is_straight),
+                HandType::groups("four of a kind", &[4]),
+                HandType::groups("three pair", &[2, 2, 2]),
+                HandType::groups("full house", &[3, 2]),
+                HandType::groups("high card", &[]),
+            ));
+
+        let expected = vec!(
+            ("222333", "two three of a kinds", None),
+            ("KKAAAJ", "two three of a kinds", Some('K')),
+            ("23456J", "straight", Some('7')),
+            ("3456J8", "straight", Some('7')),
+            ("22225K", "four of a kind", None),
+            ("22K55K", "three pair", None),
+            ("22K5KK", "full house", None),
+            ("22K5QK", "high card", None),
+        );
+        for (txt, expected, substitute) in expected {
+            let hand = rules.hand(txt).unwrap();
+            assert_eq!(expected, hand.hand_type(), "for hand: {}", txt);
+            assert_eq!(substitute, hand.substitute(), "for hand: {}", txt);
+        }
+
+        assert!(rules.hand("2345J6").unwrap() > rules.hand("AAAAKQ").unwrap());
+        let err = rules.hand("AAAAK").err().unwrap();
+        assert_eq!("AAAAK does not have exactly 6 cards", err.to_string());
+
+        let rules = rules.with_types(vec!(HandType::custom("straight", is_straight)));
+        let err = rules.hand("AAAAKQ").err().unwrap();
+        assert_eq!("AAAAKQ is not any of the types of hand", err.to_string());
+    }
+
+    #[test]
+    fn test_tournament() {
+        let hands = parse_input("32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483").unwrap();
+
+        let tournament = Tournament::new(&hands, &CardRules::jokers()).unwrap();
+        assert_eq!(5905, tournament.total_winnings());
+        assert_eq!(Ranked {
+            cards: "KTJJT".to_string(),
+            hand_type: "four of a kind",
+            substitute: Some('T'),
+            bid: 220,
+            rank: 5,
+            winnings: 1100,
+        }, tournament.hands[4]);
+
+        let text = tournament.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(" Rank  Hand   Type            Wildcards as     Bid   Winnings", lines[0]);
+        assert_eq!("    1  32T3K  one pair        -                765        765", lines[1]);
+        assert_eq!("    5  KTJJT  four of a kind  T                220       1100", lines[5]);
+        assert_eq!("Total winnings: 5905", lines[6]);
+
+        let csv = explain_tournaments(&hands, Format::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(11, lines.len());
+        assert_eq!("part,rank,hand,type,wildcards_as,bid,winnings", lines[0]);
+        assert_eq!("1,5,QQQJA,three of a kind,,483,2415", lines[5]);
+        assert_eq!("2,5,KTJJT,four of a kind,T,220,1100", lines[10]);
+
+        assert_eq!(None, explain_tournaments(&hands, Format::Json));
+    }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input("32T3K 765\nT55X5 684\n").unwrap_err();
+        assert_eq!("line 2, column 4: not a valid card", err.to_string());
+
+        let err = parse_input("32T3 765\n").unwrap_err();
+        assert_eq!("line 1, column 1: a hand must have exactly 5 cards", err.to_string());
+
+        let err = parse_input("32T3K\n").unwrap_err();
+        assert_eq!("line 1, column 1: expected a hand and a bid", err.to_string());
+    }
 }
@@ -1,178 +1,323 @@
-use std::cell::UnsafeCell;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-use typed_arena::Arena;
+
+use crate::error::{ self, Error, Line, Result };
+use crate::solver::Solver;
 
 #[derive(Debug)]
-enum Dir {
-    LEFT,
-    RIGHT,
+pub enum Dir {
+    Left,
+    Right,
 }
 
-// Modeling graphs in Rust is hard. Here is the idea I went with:
-// https://github.com/nrc/r4cppp/blob/master/graphs/README.md
-// And another option:
-// https://smallcultfollowing.com/babysteps/blog/2015/04/06/modeling-graphs-in-rust-using-vector-indices/
-struct Node<'a> {
-    name: String,
-    left_nodes: UnsafeCell<Vec<&'a Node<'a>>>,
-    right_nodes: UnsafeCell<Vec<&'a Node<'a>>>,
+/// The nodes of the map, numbered in the order they are defined. Names are
+/// interned once when parsing, so walking the map only follows indexes.
+#[derive(Debug, Default)]
+pub struct Network {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    left: Vec<usize>,
+    right: Vec<usize>,
 }
 
-impl<'a> Node<'a> {
-    fn new<'b>(name: String, arena: &'b Arena<Node<'b>>) -> &'b Node<'b> {
-        arena.alloc(Node {
-            name,
-            left_nodes: UnsafeCell::new(vec!()),
-            right_nodes: UnsafeCell::new(vec!()),
-        })
+impl Network {
+    pub fn len(&self) -> usize {
+        self.names.len()
     }
 
-    fn left(&'a self) -> &'a Node<'a> {
-        unsafe {
-            (*self.left_nodes.get())[0]
-        }
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
     }
 
-    fn right(&'a self) -> &'a Node<'a> {
-        unsafe {
-            (*self.right_nodes.get())[0]
+    /// The node reached by going `dir` from node `id`.
+    pub fn next(&self, id: usize, dir: &Dir) -> usize {
+        match dir {
+            Dir::Left => self.left[id],
+            Dir::Right => self.right[id],
         }
     }
+
+    /// The nodes whose names end with `suffix`, in the order they are defined.
+    pub fn ids_ending_with<'a>(&'a self, suffix: &'a str) -> impl Iterator<Item = usize> + 'a {
+        (0..self.len()).filter(move |&id| self.names[id].ends_with(suffix))
+    }
 }
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    let (directions, raw_nodes) = parse_input(input);
-    
-    let dir_count = directions.len();
-    let arena = Arena::new();
-    let mut roots = build_graph(raw_nodes, &arena, "AAA");
+pub struct Day08;
 
-    let mut step_count: usize = 0;
+impl Solver for Day08 {
+    type Input = (Vec<Dir>, Network);
 
-    loop {
-        let dir = &directions[step_count % dir_count];
-        step_count += 1;
+    fn day(&self) -> u32 {
+        8
+    }
 
-        let mut is_end = true;
+    fn title(&self) -> &'static str {
+        "Haunted Wasteland"
+    }
 
-        for i in 0..roots.len() {
-            let node = roots[i];
-            roots[i] = match dir {
-                Dir::LEFT => node.left(),
-                Dir::RIGHT => node.right(),
-            };
+    fn parse(&self, input: &str) -> Result<(Vec<Dir>, Network)> {
+        parse_input(input)
+    }
 
-            if !roots[i].name.ends_with("ZZZ") {
-                is_end = false;
-            }
+    fn part1(&self, (directions, network): &(Vec<Dir>, Network)) -> Result<String> {
+        solve_part1(directions, network)
+    }
 
-            // println!("Moved {:?} and reached {}. is_end={}", dir, roots[i].name, is_end);
-        }
+    fn part2(&self, (directions, network): &(Vec<Dir>, Network)) -> Result<String> {
+        solve_part2(directions, network)
+    }
+}
+
+pub(crate) fn solve_part1(directions: &[Dir], network: &Network)  -> Result<String> {
+    let mut node = network.id("AAA")
+        .ok_or_else(|| Error::solve("there is no node named AAA to start from"))?;
+    let end = network.id("ZZZ")
+        .ok_or_else(|| Error::solve("there is no node named ZZZ to reach"))?;
 
+    let mut step_count: usize = 0;
+
+    // Once every (node, instruction) pair has come up, the walk only goes
+    // round in circles, so ZZZ is reached within that many steps or never.
+    for dir in directions.iter().cycle().take(network.len() * directions.len()) {
+        node = network.next(node, dir);
+        step_count += 1;
 
-        if is_end {
-            break;
+        if node == end {
+            return Ok(step_count.to_string());
         }
     }
 
-    step_count.to_string()
+    Err(Error::solve("ZZZ cannot be reached from AAA"))
 }
 
-fn parse_input(input: Lines<BufReader<File>>) -> (Vec<Dir>, HashMap<String, (String, String)>) {
+fn parse_input(input: &str) -> Result<(Vec<Dir>, Network)> {
 
-    let mut directions: String = String::from("");
-    let mut node_data = HashMap::new();
+    let mut lines = error::lines(input);
+    let first = lines.next().ok_or_else(|| Error::parse(1, 1, "input is empty"))?;
+    let directions = parse_directions(first)?;
 
-    for line in input {
-        let line = line.unwrap();
-        
-        if directions.len() == 0 {
-            directions = line;
-            continue;
-        }
+    let mut network = Network::default();
+    let mut node_lines = vec!();
 
-        if line.len() == 0 {
+    for line in lines {
+        if line.text.is_empty() {
             continue;
         }
 
-        if !line.contains("=") {
-            panic!("Unexpected input");
+        let (key, left, right) = parse_node(line)?;
+        if network.ids.insert(key.to_string(), network.names.len()).is_some() {
+            return Err(line.error(key, format!("node {} is already defined", key)));
         }
-
-        let key = line[..3].to_string();
-        let left = line[7..10].to_string();
-        let right = line[12..15].to_string();
-
-        node_data.insert(key.clone(), (left, right));
+        network.names.push(key.to_string());
+        node_lines.push((line, left, right));
     }
 
-    let directions: Vec<Dir> = directions.chars().map(|c| match c {
-        'R' => Dir::RIGHT,
-        'L' => Dir::LEFT,
-        _ => panic!("Invalid direction")
-    }).collect();
+    // make sure every edge leads somewhere, so that walking the map never
+    // needs to check
+    for (line, left, right) in node_lines {
+        let id = |neighbor: &str| network.id(neighbor)
+            .ok_or_else(|| line.error(neighbor, format!("there is no node named {}", neighbor)));
+        let (left, right) = (id(left)?, id(right)?);
+        network.left.push(left);
+        network.right.push(right);
+    }
 
-    return (directions, node_data);
+    return Ok((directions, network));
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
-    // https://www.reddit.com/r/adventofcode/comments/18e6vdf/2023_day_8_part_2_an_explanation_for_why_the/
-    let (directions, raw_nodes) = parse_input(input);
-    
-    let dir_count = directions.len();
-    let arena = Arena::new();
-    let mut roots = build_graph(raw_nodes, &arena, "A");
-    let roots_count = roots.len();
-    let mut steps_to_destination: Vec<usize> = vec![0; roots_count];
+fn parse_directions(line: Line) -> Result<Vec<Dir>> {
+    if line.text.is_empty() {
+        return Err(line.error(line.text, "expected a list of directions"));
+    }
+    line.text.char_indices().map(|(i, c)| match c {
+        'R' => Ok(Dir::Right),
+        'L' => Ok(Dir::Left),
+        _ => Err(line.error(&line.text[i..], "invalid direction")),
+    }).collect()
+}
 
-    let mut step_count: usize = 0;
+fn parse_node<'a>(line: Line<'a>) -> Result<(&'a str, &'a str, &'a str)> {
+    let expected = || line.error(line.text, "expected a node like \"AAA = (BBB, CCC)\"");
 
-    loop {
-        let dir = &directions[step_count % dir_count];
-        step_count += 1;
+    let (key, edges) = line.text.split_once(" = ").ok_or_else(expected)?;
+    let edges = edges.strip_prefix("(").and_then(|e| e.strip_suffix(")")).ok_or_else(expected)?;
+    let (left, right) = edges.split_once(", ").ok_or_else(expected)?;
 
-        for i in 0..roots_count {
-            if steps_to_destination[i] != 0 {
-                continue;
-            }
+    Ok((key, left, right))
+}
 
-            let node = roots[i];
-            roots[i] = match dir {
-                Dir::LEFT => node.left(),
-                Dir::RIGHT => node.right(),
-            };
+/// Where one ghost's walk goes, as steps from the start. A ghost's state is
+/// its node and its place in the directions, so after at most as many steps
+/// as there are states it starts going round a cycle.
+#[derive(Debug, PartialEq)]
+pub struct Ghost {
+    /// Steps taken before the cycle starts.
+    pub tail: usize,
+    /// Steps taken to go once round the cycle.
+    pub cycle: usize,
+    /// Steps before the cycle starts at which the ghost is on an end node.
+    pub tail_ends: Vec<usize>,
+    /// How many steps into each trip round the cycle the ghost is on an end
+    /// node.
+    pub cycle_ends: Vec<usize>,
+}
 
-            if roots[i].name.ends_with("Z") {
-               steps_to_destination[i] = step_count; 
+impl Ghost {
+    pub fn walk(directions: &[Dir], network: &Network, start: usize, is_end: &[bool]) -> Self {
+        let dir_count = directions.len();
+        let mut first_seen = vec![usize::MAX; network.len() * dir_count];
+        let mut ends = vec!();
+        let mut node = start;
+
+        for step in 0.. {
+            let state = node * dir_count + step % dir_count;
+            if first_seen[state] != usize::MAX {
+                let tail = first_seen[state];
+                let (tail_ends, cycle_ends) = ends.iter().partition::<Vec<usize>, _>(|&&end| end < tail);
+                return Ghost {
+                    tail,
+                    cycle: step - tail,
+                    tail_ends,
+                    cycle_ends: cycle_ends.iter().map(|end| end - tail).collect(),
+                };
+            }
+            first_seen[state] = step;
+            if is_end[node] {
+                ends.push(step);
             }
+            node = network.next(node, &directions[step % dir_count]);
+        }
+        unreachable!("a walk always comes back to a state it has been in")
+    }
 
-            // println!("Moved {:?} and reached {}. is_end={}", dir, roots[i].name, is_end);
+    /// Whether the ghost is on an end node after `step` steps.
+    fn is_at_end(&self, step: usize) -> bool {
+        if step < self.tail {
+            self.tail_ends.contains(&step)
+        } else {
+            self.cycle_ends.contains(&((step - self.tail) % self.cycle))
         }
+    }
+}
+
+pub(crate) fn solve_part2(directions: &[Dir], network: &Network)  -> Result<String> {
+    let starts: Vec<usize> = network.ids_ending_with("A").collect();
+    if starts.is_empty() {
+        return Err(Error::solve("there are no nodes ending in A to start from"));
+    }
+    let is_end: Vec<bool> = (0..network.len()).map(|id| network.name(id).ends_with("Z")).collect();
+
+    let ghosts: Vec<Ghost> = starts.iter()
+        .map(|&start| Ghost::walk(directions, network, start, &is_end))
+        .collect();
+
+    first_step_all_at_end(&ghosts)?
+        .map(|step| step.to_string())
+        .ok_or_else(|| Error::solve("the ghosts are never all on nodes ending in Z at the same time"))
+}
 
+/// The fewest steps, at least one, after which every ghost is on an end node
+/// at once, or None if that never happens.
+fn first_step_all_at_end(ghosts: &[Ghost]) -> Result<Option<u128>> {
+    // before every ghost has reached its cycle, try each time the first ghost
+    // is at an end
+    let longest_tail = ghosts.iter().map(|ghost| ghost.tail).max().unwrap_or(0).max(1);
+    let first = &ghosts[0];
+    let early = (1..longest_tail)
+        .filter(|&step| first.is_at_end(step))
+        .find(|&step| ghosts.iter().all(|ghost| ghost.is_at_end(step)));
+    if let Some(step) = early {
+        return Ok(Some(step as u128));
+    }
 
-        if steps_to_destination.iter().all(|&x| x > 0) {
-            break;
+    // after that every ghost is going round its cycle, so each is at an end
+    // exactly when the step is one of a few remainders modulo its cycle;
+    // combine them with the Chinese remainder theorem
+    let mut modulus: u128 = 1;
+    let mut remainders: Vec<u128> = vec!(0);
+    for ghost in ghosts {
+        let cycle = ghost.cycle as u128;
+        let ends: Vec<u128> = ghost.cycle_ends.iter().map(|&end| (ghost.tail + end) as u128 % cycle).collect();
+
+        let mut combined = vec!();
+        for &r in &remainders {
+            for &end in &ends {
+                if let Some((remainder, _)) = crt(r, modulus, end, cycle)? {
+                    combined.push(remainder);
+                }
+            }
+        }
+        if combined.is_empty() {
+            return Ok(None);
         }
+        modulus = lcm(modulus, cycle)?;
+        combined.sort_unstable();
+        combined.dedup();
+        remainders = combined;
+    }
+
+    // the smallest step from the longest tail onwards with one of the
+    // remainders
+    let start = longest_tail as u128;
+    Ok(remainders.iter()
+        .map(|&r| start + (r + modulus - start % modulus) % modulus)
+        .min())
+}
+
+/// The step `x` with `x ≡ a (mod m)` and `x ≡ b (mod n)`, as a remainder
+/// modulo lcm(m, n), or None if there is no such step.
+fn crt(a: u128, m: u128, b: u128, n: u128) -> Result<Option<(u128, u128)>> {
+    let g = gcd(m, n);
+    let (a, b) = (a % m, b % n);
+    if a % g != b % g {
+        return Ok(None);
     }
+    let l = lcm(m, n)?;
+
+    // x = a + m * k, where m * k ≡ b - a (mod n), so k ≡ (b - a) / g * (m / g)⁻¹ (mod n / g)
+    let n_g = n / g;
+    let diff = ((b + n - a % n) % n) / g;
+    let k = mul_mod(diff % n_g, mod_inverse(m / g % n_g, n_g), n_g);
+    let x = (a + mul_mod(m, k, l)) % l;
+    Ok(Some((x, l)))
+}
 
-    lcm_vec(steps_to_destination).to_string()
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    // the moduli here come from walk lengths, so they fit in a u64 and
+    // products of their remainders fit in a u128
+    (a % m) * (b % m) % m
 }
 
-fn lcm_vec(nums: Vec<usize>) -> usize {
-    let mut accum = nums[0];
-    for i in 1..nums.len() {
-        accum = lcm(accum, nums[i]);
+/// The inverse of `a` modulo `m`, for `a` and `m` with no common factor.
+fn mod_inverse(a: u128, m: u128) -> u128 {
+    if m == 1 {
+        return 0;
+    }
+    let (mut old_r, mut r) = (a as i128, m as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
     }
-    accum
+    old_s.rem_euclid(m as i128) as u128
 }
 
-fn lcm(a: usize, b: usize) -> usize {
-    a * b / gcd(a, b)
+fn lcm(a: u128, b: u128) -> Result<u128> {
+    (a / gcd(a, b)).checked_mul(b)
+        .filter(|&l| l <= u64::MAX as u128)
+        .ok_or_else(|| Error::solve("the ghosts' cycles are too long to combine"))
 }
 
-fn gcd(a: usize, b: usize) -> usize {
+fn gcd(a: u128, b: u128) -> u128 {
     let mut aa = a;
     let mut bb = b;
     while bb > 0 {
@@ -181,56 +326,180 @@ fn gcd(a: usize, b: usize) -> usize {
     aa
 }
 
-fn build_graph<'a>(raw_nodes: HashMap<String, (String, String)>, arena: &'a Arena<Node<'a>>, start_node_suffix: &str) -> Vec<&'a Node<'a>> {
-    let mut nodes = HashMap::new();
 
-    let mut roots: Vec<&'a Node<'a>> = vec!();
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_build_graph() {
+        let (_, network) = parse_input("L\n\nAAA = (BBB, CCC)\nBBB = (AAA, CCC)\nCCC = (AAA, BBB)\n").unwrap();
+
+        let aaa = network.id("AAA").unwrap();
+        let bbb = network.next(aaa, &Dir::Left);
+        let ccc = network.next(aaa, &Dir::Right);
+        assert_eq!(3, network.len());
+        assert_eq!("BBB", network.name(bbb));
+        assert_eq!("CCC", network.name(ccc));
+        assert_eq!("AAA", network.name(network.next(bbb, &Dir::Left)));
+        assert_eq!("CCC", network.name(network.next(bbb, &Dir::Right)));
+        assert_eq!("AAA", network.name(network.next(ccc, &Dir::Left)));
+        assert_eq!("BBB", network.name(network.next(ccc, &Dir::Right)));
+        assert_eq!(None, network.id("ZZZ"));
+        assert_eq!(vec!(aaa), network.ids_ending_with("A").collect::<Vec<usize>>());
+    }
+
+    const OFFSET_CYCLES: &str = "L
+
+AAA = (PBB, PBB)
+PBB = (PCC, PCC)
+PCC = (PZZ, PZZ)
+PZZ = (PDD, PDD)
+PDD = (PBB, PBB)
+QQA = (QBB, QBB)
+QBB = (QZZ, QZZ)
+QZZ = (QDD, QDD)
+QDD = (QBB, QBB)
+";
+
+    #[test]
+    fn test_part1_unreachable() {
+        let (directions, network) = parse_input("LR\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)\n").unwrap();
+        let err = solve_part1(&directions, &network).unwrap_err();
+        assert_eq!("ZZZ cannot be reached from AAA", err.to_string());
+
+        let (directions, network) = parse_input("L\n\nAAA = (BBB, AAA)\nBBB = (ZZZ, AAA)\nZZZ = (ZZZ, ZZZ)\n").unwrap();
+        assert_eq!("2", solve_part1(&directions, &network).unwrap());
+    }
+
+    #[test]
+    fn test_ghost_walk() {
+        let (directions, network) = parse_input(OFFSET_CYCLES).unwrap();
+        let is_end: Vec<bool> = (0..network.len()).map(|id| network.name(id).ends_with("Z")).collect();
+
+        let ghost = Ghost::walk(&directions, &network, network.id("AAA").unwrap(), &is_end);
+        assert_eq!(Ghost { tail: 1, cycle: 4, tail_ends: vec!(), cycle_ends: vec!(2) }, ghost);
+        assert!(ghost.is_at_end(3));
+        assert!(ghost.is_at_end(7));
+        assert!(!ghost.is_at_end(6));
+
+        let ghost = Ghost::walk(&directions, &network, network.id("QQA").unwrap(), &is_end);
+        assert_eq!(Ghost { tail: 1, cycle: 3, tail_ends: vec!(), cycle_ends: vec!(1) }, ghost);
+
+        // a ghost that only passes an end node before its cycle
+        let (directions, network) = parse_input("LR\n\nAAA = (XXZ, XXZ)\nXXZ = (BBB, BBB)\nBBB = (BBB, BBB)\n").unwrap();
+        let is_end: Vec<bool> = (0..network.len()).map(|id| network.name(id).ends_with("Z")).collect();
+        let ghost = Ghost::walk(&directions, &network, network.id("AAA").unwrap(), &is_end);
+        assert_eq!(Ghost { tail: 2, cycle: 2, tail_ends: vec!(1), cycle_ends: vec!() }, ghost);
+    }
+
+    #[test]
+    fn test_part2_without_the_lcm_shortcut() {
+        // the first end nodes are 3 and 2 steps away, but the ghosts only
+        // meet there after 11 steps
+        let (directions, network) = parse_input(OFFSET_CYCLES).unwrap();
+        assert_eq!("11", solve_part2(&directions, &network).unwrap());
+
+        // one ghost is at an end after an odd number of steps, the other
+        // after an even number
+        let input = OFFSET_CYCLES.replace("QZZ = (QDD, QDD)", "QZZ = (QBB, QBB)");
+        let (directions, network) = parse_input(&input).unwrap();
+        let err = solve_part2(&directions, &network).unwrap_err();
+        assert_eq!("the ghosts are never all on nodes ending in Z at the same time", err.to_string());
+
+        // both ghosts pass an end node together before reaching their cycles
+        let (directions, network) = parse_input("L\n\nAAA = (ABZ, ABZ)\nABZ = (ACC, ACC)\nACC = (ACC, ACC)\nBBA = (BBZ, BBZ)\nBBZ = (BCC, BCC)\nBCC = (BDD, BDD)\nBDD = (BCC, BCC)\n").unwrap();
+        assert_eq!("1", solve_part2(&directions, &network).unwrap());
+    }
+
+    #[test]
+    fn test_several_ends_per_cycle() {
+        // the first ghost is at an end after 3, 5, 8, 10, 13, ... steps, and
+        // the second after every 4th step, so they first meet after 8
+        let input = "L
+
+AAA = (ABB, ABB)
+ABB = (ACC, ACC)
+ACC = (ADZ, ADZ)
+ADZ = (AEE, AEE)
+AEE = (AFZ, AFZ)
+AFZ = (ABB, ABB)
+BBA = (BCC, BCC)
+BCC = (BDD, BDD)
+BDD = (BEE, BEE)
+BEE = (BFZ, BFZ)
+BFZ = (BCC, BCC)
+";
+        let (directions, network) = parse_input(input).unwrap();
+        assert_eq!("8", solve_part2(&directions, &network).unwrap());
+    }
 
-    // create empty nodes
-    for name in raw_nodes.keys().cloned() {
-        let n = nodes.entry(name.clone()).or_insert(Node::new(name.clone(), arena));
-        if name.ends_with(start_node_suffix) {
-            roots.push(n);
+    /// Walk every ghost a step at a time, giving up after `limit` steps.
+    fn solve_part2_by_walking(directions: &[Dir], network: &Network, limit: usize) -> Option<usize> {
+        let mut nodes: Vec<usize> = network.ids_ending_with("A").collect();
+        for step in 1..=limit {
+            let dir = &directions[(step - 1) % directions.len()];
+            for node in nodes.iter_mut() {
+                *node = network.next(*node, dir);
+            }
+            if nodes.iter().all(|&node| network.name(node).ends_with("Z")) {
+                return Some(step);
+            }
         }
+        None
     }
 
-    // create edges
-    unsafe {
-        for (name, (left_key, right_key)) in raw_nodes {
-            let left = *(nodes.get(&left_key).unwrap());
-            let right = *(nodes.get(&right_key).unwrap());
-            nodes.entry(name).and_modify(|n| {
-                (*n.left_nodes.get()).push(left);
-                (*n.right_nodes.get()).push(right);
-            });
+    proptest! {
+        #[test]
+        fn test_part2_matches_walking(
+            directions in "[LR]{1,3}",
+            edges in prop::collection::vec((0usize..8, 0usize..8, 0usize..3), 8),
+        ) {
+            // nodes 0 to 2 are starts, and each node is an end if its third
+            // value is 0
+            let name = |i: usize| match (i, edges[i].2) {
+                (0..=2, _) => format!("S{}A", i),
+                (_, 0) => format!("N{}Z", i),
+                _ => format!("N{}B", i),
+            };
+            let mut input = format!("{}\n\n", directions);
+            for (i, (left, right, _)) in edges.iter().enumerate() {
+                input += &format!("{} = ({}, {})\n", name(i), name(*left), name(*right));
+            }
+            let (directions, network) = parse_input(&input).unwrap();
+
+            // each ghost has at most 24 states, so it is in its cycle within
+            // 24 steps, and three cycles of at most 24 steps line up within
+            // 24 × 23 × 22 steps after that if they ever do
+            let expected = solve_part2_by_walking(&directions, &network, 20_000);
+            let actual = solve_part2(&directions, &network).ok();
+            prop_assert_eq!(expected.map(|step| step.to_string()), actual);
         }
     }
 
-    roots
-}
+    #[test]
+    fn test_crt() {
+        assert_eq!(Some((11, 12)), crt(3, 4, 2, 3).unwrap());
+        assert_eq!(Some((10, 12)), crt(4, 6, 2, 4).unwrap());
+        assert_eq!(None, crt(1, 4, 2, 6).unwrap());
+        assert_eq!(Some((0, 6)), crt(0, 2, 0, 3).unwrap());
+        assert_eq!(Some((5, 7)), crt(5, 7, 0, 1).unwrap());
+    }
 
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input("LLR\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\n").unwrap_err();
+        assert_eq!("line 4, column 13: there is no node named ZZZ", err.to_string());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let err = parse_input("LLR\n\nAAA = BBB, BBB\n").unwrap_err();
+        assert_eq!("line 3, column 1: expected a node like \"AAA = (BBB, CCC)\"", err.to_string());
 
-    #[test]
-    fn test_build_graph() {
-        let mut raw_nodes = HashMap::new();
-        raw_nodes.insert("AAA".to_string(), ("BBB".to_string(), "CCC".to_string()));
-        raw_nodes.insert("BBB".to_string(), ("AAA".to_string(), "CCC".to_string()));
-        raw_nodes.insert("CCC".to_string(), ("AAA".to_string(), "BBB".to_string()));
-
-        let arena = Arena::new();
-        let g = build_graph(raw_nodes, &arena, "AAA");
-
-        let bbb = g[0].left();
-        let ccc = g[0].right();
-        assert_eq!("BBB".to_string(), bbb.name);
-        assert_eq!("CCC".to_string(), ccc.name);
-        assert_eq!("AAA".to_string(), bbb.left().name);
-        assert_eq!("CCC".to_string(), bbb.right().name);
-        assert_eq!("AAA".to_string(), ccc.left().name);
-        assert_eq!("BBB".to_string(), ccc.right().name);
+        let err = parse_input("LRX\n\nAAA = (AAA, AAA)\n").unwrap_err();
+        assert_eq!("line 1, column 3: invalid direction", err.to_string());
+
+        let err = parse_input("LR\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)\n").unwrap_err();
+        assert_eq!("line 4, column 1: node AAA is already defined", err.to_string());
     }
 }
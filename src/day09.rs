@@ -1,20 +1,46 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
+use crate::error::{ self, Line, Result };
+use crate::solver::Solver;
+
+pub struct Day09;
+
+impl Solver for Day09 {
+    type Input = Vec<Vec<i32>>;
+
+    fn day(&self) -> u32 {
+        9
+    }
+
+    fn title(&self) -> &'static str {
+        "Mirage Maintenance"
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<Vec<i32>>> {
+        error::lines(input).map(to_int_vec).collect()
+    }
+
+    fn part1(&self, sequences: &Vec<Vec<i32>>) -> Result<String> {
+        Ok(solve_part1(sequences))
+    }
+
+    fn part2(&self, sequences: &Vec<Vec<i32>>) -> Result<String> {
+        Ok(solve_part2(sequences))
+    }
+}
+
+pub(crate) fn solve_part1(sequences: &[Vec<i32>])  -> String {
     let mut ttl = 0;
-    for line in input {
-        let seq = to_int_vec(&line.unwrap());
-        ttl += get_next_value(seq);
+    for seq in sequences {
+        ttl += get_next_value(seq.clone());
     }
 
     return ttl.to_string()
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
+pub(crate) fn solve_part2(sequences: &[Vec<i32>])  -> String {
     let mut ttl = 0;
-    for line in input {
-        let mut seq = to_int_vec(&line.unwrap());
+    for seq in sequences {
+        let mut seq = seq.clone();
         seq.reverse();
         ttl += get_next_value(seq);
     }
@@ -22,8 +48,11 @@ pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
     return ttl.to_string()
 }
 
-fn to_int_vec(line: &String) -> Vec<i32> {
-    line.split(" ").map(|x| x.parse::<i32>().unwrap()).collect()
+fn to_int_vec(line: Line) -> Result<Vec<i32>> {
+    if line.text.is_empty() {
+        return Err(line.error(line.text, "expected a sequence of numbers"));
+    }
+    line.text.split(" ").map(|x| line.parse::<i32>(x)).collect()
 }
 
 fn get_next_value(seq: Vec<i32>) -> i32 {
@@ -1,7 +1,8 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
 use std::collections::HashMap;
 
+use crate::error::{ Error, Result };
+use crate::solver::Solver;
+
 const VPIPE: char = '|';
 const HPIPE: char = '-';
 const NORTH_EAST_PIPE: char = 'L';
@@ -42,7 +43,7 @@ struct Neighbors {
     east: bool,
 }
 
-struct Grid {
+pub struct Grid {
     data: HashMap<Coord, Pipe>,
     start_coord: Option<Coord>,
     width: usize,
@@ -68,24 +69,48 @@ impl Grid {
     }
 }
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>) -> String {
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    let grid = parse_input(data);
+pub struct Day10;
+
+impl Solver for Day10 {
+    type Input = Grid;
+
+    fn day(&self) -> u32 {
+        10
+    }
+
+    fn title(&self) -> &'static str {
+        "Pipe Maze"
+    }
+
+    fn parse(&self, input: &str) -> Result<Grid> {
+        parse_input(input.lines().map(String::from).collect())
+    }
+
+    fn part1(&self, grid: &Grid) -> Result<String> {
+        solve_part1(grid)
+    }
+
+    fn part2(&self, grid: &Grid) -> Result<String> {
+        solve_part2(grid)
+    }
+}
+
+pub(crate) fn solve_part1(grid: &Grid) -> Result<String> {
     return part_1_solver(grid);
 }
 
-fn part_1_solver(grid: Grid) -> String {
+fn part_1_solver(grid: &Grid) -> Result<String> {
     let mut path: Vec<Coord> = vec!();
-    let start_coord = grid.start_coord.expect("Can't solve part 1 without a start node");
+    let start_coord = grid.start_coord.ok_or_else(|| Error::solve("can't solve part 1 without a start node"))?;
     let mut current_node = start_coord;
     let mut prev = Coord::new(grid.height + 10, grid.width + 10);
     let mut finished = false;
 
     while !finished {
         let current_pipe = grid.get(&current_node).expect("Got invalid node!?");
-        let neighbors = get_connections(&current_pipe, &grid);
-        println!("At {:?}, got neighbors: {:?}. prev={:?}", &current_node, &neighbors, &prev);
+        let neighbors = get_connections(current_pipe, grid);
         path.push(current_node);
+        let mut moved = false;
 
         for neighbor in neighbors {
             // println!("At {:?} evaluating neighbor {:?}", current_node, neighbor);
@@ -98,28 +123,35 @@ fn part_1_solver(grid: Grid) -> String {
             }
             prev = current_node;
             current_node = neighbor;
+            moved = true;
             break;
         }
-        
+
+        if !finished && !moved {
+            return Err(Error::solve(format!("the pipe loop is broken at row {}, column {}", current_node.row + 1, current_node.col + 1)));
+        }
     }
     // println!("Found cycle with length: {}", path.len());
     let result = path.len() / 2;
 
-    return result.to_string();
+    return Ok(result.to_string());
 }
 
-pub(crate) fn solve_part2(_input: Lines<BufReader<File>>)  -> String {
-    return String::from("TODO");
+pub(crate) fn solve_part2(_grid: &Grid)  -> Result<String> {
+    return Err(Error::Unsolved);
 }
 
-fn parse_input(input: Vec<String>) -> Grid {
-    let width = input.get(0).expect("No data in input!").len();
+fn parse_input(input: Vec<String>) -> Result<Grid> {
+    let width = input.first().ok_or_else(|| Error::parse(1, 1, "no data in input"))?.len();
     let height = input.len();
     let mut grid = Grid::new(width, height);
 
     let mut start_coord = Coord::new(0, 0);
 
     for (rownum, row) in input.iter().enumerate() {
+        if row.len() != width {
+            return Err(Error::parse(rownum + 1, 1, format!("expected a row of width {}", width)));
+        }
         for (colnum, value) in row.chars().enumerate() {
             let coord = Coord { row: rownum, col: colnum };
             grid.insert(coord, Pipe::new(coord, value));
@@ -133,10 +165,10 @@ fn parse_input(input: Vec<String>) -> Grid {
     match grid.get(&start_coord) {
         Some(sp) => {
             if sp.value != START_PIPE {
-                return grid;
+                return Ok(grid);
             }
         },
-        None => return grid,
+        None => return Ok(grid),
     }
 
     // Special handling for the starting coordinate. We need to look
@@ -167,7 +199,7 @@ fn parse_input(input: Vec<String>) -> Grid {
     grid.insert(start_coord, Pipe::new(start_coord, start_node_type));
     grid.start_coord = Option::Some(start_coord);
 
-    return grid
+    return Ok(grid)
 }
 
 fn get_neighbors(coord: &Coord, grid: &Grid) -> Neighbors {
@@ -264,11 +296,10 @@ mod tests {
             String::from(".L-J."),
             String::from("....."),
         );
-        let grid = parse_input(input);
+        let grid = parse_input(input).unwrap();
 
         for col in 0..4 {
             let coord = Coord::new(0, col);
-            println!("Getting pipe at coord: {:?}", coord);
             let pipe = grid.get(&coord).unwrap();
             assert_eq!(coord, pipe.coord);
         }
@@ -368,7 +399,7 @@ mod tests {
     }
 
     fn parse_input_start_node(input: Vec<String>, location: Coord, expected_value: char) {
-        let grid = parse_input(input);
+        let grid = parse_input(input).unwrap();
         let pipe = grid.get(&location).unwrap();
         assert_eq!(expected_value, pipe.value);
         assert_eq!(location, grid.start_coord.unwrap());
@@ -383,8 +414,8 @@ mod tests {
             String::from(".L-J."),
             String::from("....."),
         );
-        let grid = parse_input(input);
-        let result = part_1_solver(grid);
+        let grid = parse_input(input).unwrap();
+        let result = part_1_solver(&grid).unwrap();
         assert_eq!(String::from("4"), result);
     }
 
@@ -397,8 +428,18 @@ mod tests {
             String::from("|F--J"),
             String::from("LJ..."),
         );
-        let grid = parse_input(input);
-        let result = part_1_solver(grid);
+        let grid = parse_input(input).unwrap();
+        let result = part_1_solver(&grid).unwrap();
         assert_eq!(String::from("8"), result);
     }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let input = vec!(
+            String::from("-L|F7"),
+            String::from("7S-7"),
+        );
+        let err = parse_input(input).err().unwrap();
+        assert_eq!("line 2, column 1: expected a row of width 5", err.to_string());
+    }
 }
@@ -1,25 +1,62 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
+
+use crate::error::{ self, Error, Result };
+use crate::solver::Solver;
 
 type Coord = (usize, usize);
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    return part_1_solver(data).to_string();
-    
+pub struct Day11;
+
+impl Solver for Day11 {
+    type Input = Vec<String>;
+
+    fn day(&self) -> u32 {
+        11
+    }
+
+    fn title(&self) -> &'static str {
+        "Cosmic Expansion"
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<String>> {
+        parse_input(input)
+    }
+
+    fn part1(&self, image: &Vec<String>) -> Result<String> {
+        Ok(solve_part1(image))
+    }
+
+    fn part2(&self, image: &Vec<String>) -> Result<String> {
+        solve_part2(image)
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<String>> {
+    let width = input.lines().next().ok_or_else(|| Error::parse(1, 1, "input is empty"))?.len();
+
+    for line in error::lines(input) {
+        if line.text.len() != width {
+            return Err(line.error(line.text, format!("expected a row of width {}", width)));
+        }
+        if let Some(i) = line.text.find(|c| c != '.' && c != '#') {
+            return Err(line.error(&line.text[i..], "expected '.' or '#'"));
+        }
+    }
+
+    Ok(input.lines().map(String::from).collect())
 }
 
-fn part_1_solver(mut data: Vec<String>) -> usize {
+pub(crate) fn solve_part1(image: &[String])  -> String {
+    let mut data = image.to_vec();
     expand_cols(&mut data);
     expand_rows(&mut data);
     let galaxies = collect_galaxies(&data);
     let pairs = collect_pairs(&galaxies);
     let distances = taxicab_distances(pairs);
-    distances.iter().sum()
+    distances.iter().sum::<usize>().to_string()
 }
 
-pub(crate) fn solve_part2(_input: Lines<BufReader<File>>)  -> String {
-    return String::from("TODO");
+pub(crate) fn solve_part2(_image: &[String])  -> Result<String> {
+    return Err(Error::Unsolved);
 }
 
 fn expand_cols(data: &mut Vec<String>) {
@@ -200,19 +237,27 @@ mod tests {
 
     #[test]
     fn test_part_1_solver() {
-        let input = vec!(
-            String::from("...#......"),
-            String::from(".......#.."),
-            String::from("#........."),
-            String::from(".........."),
-            String::from("......#..."),
-            String::from(".#........"),
-            String::from(".........#"),
-            String::from(".........."),
-            String::from(".......#.."),
-            String::from("#...#....."),
-        );
-        let result = part_1_solver(input);
-        assert_eq!(374, result);
+        let input = [
+            "...#......",
+            ".......#..",
+            "#.........",
+            "..........",
+            "......#...",
+            ".#........",
+            ".........#",
+            "..........",
+            ".......#..",
+            "#...#.....",
+        ].map(String::from);
+        assert_eq!("374", solve_part1(&input));
+    }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input("...#\n.#.\n").unwrap_err();
+        assert_eq!("line 2, column 1: expected a row of width 4", err.to_string());
+
+        let err = parse_input("...#\n.#*.\n").unwrap_err();
+        assert_eq!("line 2, column 3: expected '.' or '#'", err.to_string());
     }
 }
@@ -1,46 +1,71 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
 use lazy_static::lazy_static;
 
 use regex::Regex;
 
+use crate::error::{ self, Error, Result };
+use crate::solver::Solver;
+
 lazy_static! {
     pub static ref HASH_RE: Regex = Regex::new(r"(#+)").unwrap();
 }
 
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    let data = parse_input(data);
-    let result = part_1_solver(data);
-    return result.to_string();
+pub struct Day12;
+
+impl Solver for Day12 {
+    type Input = Vec<(Vec<char>, Vec<usize>)>;
+
+    fn day(&self) -> u32 {
+        12
+    }
+
+    fn title(&self) -> &'static str {
+        "Hot Springs"
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<(Vec<char>, Vec<usize>)>> {
+        parse_input(input)
+    }
+
+    fn part1(&self, records: &Vec<(Vec<char>, Vec<usize>)>) -> Result<String> {
+        Ok(solve_part1(records))
+    }
+
+    fn part2(&self, records: &Vec<(Vec<char>, Vec<usize>)>) -> Result<String> {
+        solve_part2(records)
+    }
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
-    // for line in input {
-    //     println!("{}", line.unwrap());
-    // }
+pub(crate) fn solve_part1(records: &[(Vec<char>, Vec<usize>)])  -> String {
+    let result = part_1_solver(records);
+    return result.to_string();
+}
 
-    return String::from("");
+pub(crate) fn solve_part2(_records: &[(Vec<char>, Vec<usize>)])  -> Result<String> {
+    return Err(Error::Unsolved);
 }
 
-fn part_1_solver(data: Vec<(Vec<char>, Vec<usize>)>) -> usize {
+fn part_1_solver(data: &[(Vec<char>, Vec<usize>)]) -> usize {
     // Brute force. Slow, but it works!
     
     let mut ttl = 0;
     for (c, d) in data {
         let mut c = c.clone();
-        ttl += count_matches(&mut c, &d);
+        ttl += count_matches(&mut c, d);
     }
     return ttl;
 }
 
-fn parse_input(data: Vec<String>) -> Vec<(Vec<char>, Vec<usize>)> {
-    data.iter().map(|row| {
-        let mut s = row.split(" ");
-        let chars = s.next().unwrap().chars().collect();
-        let seq: Vec<usize> = s.next().unwrap().split(",").map(|x| x.parse::<usize>().unwrap()).collect();
-        (chars, seq)
+fn parse_input(input: &str) -> Result<Vec<(Vec<char>, Vec<usize>)>> {
+    error::lines(input).map(|row| {
+        let (conditions, sequences) = row.text.split_once(" ")
+            .ok_or_else(|| row.error(row.text, "expected spring conditions followed by group sizes"))?;
+        if let Some(i) = conditions.find(|c| !"?#.".contains(c)) {
+            return Err(row.error(&conditions[i..], "expected '?', '#' or '.'"));
+        }
+        let chars = conditions.chars().collect();
+        let seq: Vec<usize> = sequences.split(",").map(|x| row.parse::<usize>(x)).collect::<Result<_>>()?;
+        Ok((chars, seq))
     }).collect()
 }
 
@@ -99,4 +124,13 @@ mod tests {
         conditions = vec!('?','#','#','#','?','?','?','?','?','?','?','?');
         assert_eq!(10, count_matches(&mut conditions, &broken_sequences));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input("???.### 1,1,3\n.??..??...?##.\n").unwrap_err();
+        assert_eq!("line 2, column 1: expected spring conditions followed by group sizes", err.to_string());
+
+        let err = parse_input("???.### 1,x,3\n").unwrap_err();
+        assert_eq!("line 1, column 11: expected a number, found \"x\"", err.to_string());
+    }
+}
@@ -1,48 +1,76 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
+use crate::error::{ self, Error, Result };
+use crate::solver::Solver;
+
+pub struct Day13;
+
+impl Solver for Day13 {
+    type Input = Vec<Vec<String>>;
+
+    fn day(&self) -> u32 {
+        13
+    }
+
+    fn title(&self) -> &'static str {
+        "Point of Incidence"
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<Vec<String>>> {
+        parse_input(input)
+    }
+
+    fn part1(&self, blocks: &Vec<Vec<String>>) -> Result<String> {
+        solve_part1(blocks)
+    }
+
+    fn part2(&self, blocks: &Vec<Vec<String>>) -> Result<String> {
+        solve_part2(blocks)
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<Vec<String>>> {
     let mut blocks: Vec<Vec<String>> = vec!();
     let mut block: Vec<String> = vec!();
 
-    for line in input {
-        let l = line.unwrap();
+    for line in error::lines(input) {
+        let l = line.text.to_string();
         if l.is_empty() {
-            blocks.push(block);
+            if !block.is_empty() {
+                blocks.push(block);
+            }
             block = vec!();
         } else {
+            if let Some(first) = block.first() {
+                if first.len() != l.len() {
+                    return Err(line.error(line.text, format!("expected a row of width {}", first.len())));
+                }
+            }
             block.push(l);
         }
     }
     if !block.is_empty() {
         blocks.push(block);
     }
-    
+    Ok(blocks)
+}
+
+pub(crate) fn solve_part1(blocks: &[Vec<String>])  -> Result<String> {
 
     let mut ttl = 0;
 
-    for block in blocks {
-        match find_vertical_reflection(&block) {
-            Some(c) => {
-                ttl += c;
-                continue;
-            },
-            None => (),
-        }
-        match find_horizontal_reflection(&block) {
-            Some(r) => {
-                ttl += 100*r;
-                continue;
-            },
-            None => (),
+    for (i, block) in blocks.iter().enumerate() {
+        if let Some(c) = find_vertical_reflection(block) {
+            ttl += c;
+            continue;
         }
-        for l in block {
-            println!("{}", l);
+        if let Some(r) = find_horizontal_reflection(block) {
+            ttl += 100*r;
+            continue;
         }
-        panic!("Did not find any reflection lines!");
+        return Err(Error::solve(format!("did not find any reflection lines in pattern {}", i + 1)));
     }
 
-    return ttl.to_string();
+    return Ok(ttl.to_string());
 }
 
 fn find_vertical_reflection(block: &Vec<String>) -> Option<usize> {
@@ -100,8 +128,8 @@ fn is_reflection_point(s: &str, i: usize) -> bool {
     return prefix.starts_with(&suffix);
 }
 
-pub(crate) fn solve_part2(_input: Lines<BufReader<File>>)  -> String {
-    return String::from("TODO");
+pub(crate) fn solve_part2(_blocks: &[Vec<String>])  -> Result<String> {
+    return Err(Error::Unsolved);
 }
 
 #[cfg(test)]
@@ -182,4 +210,17 @@ mod tests {
             None => panic!("Expected a Some result!"),
         }
     }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input("#.##\n..#.\n\n#...\n#..\n").unwrap_err();
+        assert_eq!("line 5, column 1: expected a row of width 4", err.to_string());
+    }
+
+    #[test]
+    fn test_no_reflection() {
+        let blocks = parse_input("#.#\n...\n\n#.\n.#\n").unwrap();
+        let err = solve_part1(&blocks).unwrap_err();
+        assert_eq!("did not find any reflection lines in pattern 1", err.to_string());
+    }
 }
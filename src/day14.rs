@@ -1,7 +1,8 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
 use std::fmt;
 
+use crate::error::{ Error, Result };
+use crate::solver::Solver;
+
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
 enum DishObject {
     Empty,
@@ -9,8 +10,8 @@ enum DishObject {
     SquareRock,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-struct Grid {
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Grid {
     data: Vec<DishObject>,
     width: usize,
 }
@@ -104,32 +105,63 @@ impl fmt::Display for Grid {
     }
 }
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    let mut grid = parse_input(data);
+pub struct Day14;
+
+impl Solver for Day14 {
+    type Input = Grid;
+
+    fn day(&self) -> u32 {
+        14
+    }
+
+    fn title(&self) -> &'static str {
+        "Parabolic Reflector Dish"
+    }
+
+    fn parse(&self, input: &str) -> Result<Grid> {
+        parse_input(input.lines().map(String::from).collect())
+    }
+
+    fn part1(&self, grid: &Grid) -> Result<String> {
+        Ok(solve_part1(grid))
+    }
+
+    fn part2(&self, grid: &Grid) -> Result<String> {
+        solve_part2(grid)
+    }
+}
+
+pub(crate) fn solve_part1(grid: &Grid)  -> String {
+    let mut grid = grid.clone();
     grid.slide_rocks_north();
     return grid.get_load().to_string();
 }
 
-pub(crate) fn solve_part2(_input: Lines<BufReader<File>>)  -> String {
-    return String::from("TODO");
+pub(crate) fn solve_part2(_grid: &Grid)  -> Result<String> {
+    return Err(Error::Unsolved);
 }
 
-fn parse_input(input: Vec<String>) -> Grid {
-    let width = input.get(0).expect("No input data!").len();
+fn parse_input(input: Vec<String>) -> Result<Grid> {
+    let width = input.first().ok_or_else(|| Error::parse(1, 1, "no input data"))?.len();
+    if width == 0 {
+        return Err(Error::parse(1, 1, "expected a row of at least one tile"));
+    }
     let mut data: Vec<DishObject> = vec!();
-    for line in input {
-        for c in line.chars() {
+    for (row, line) in input.iter().enumerate() {
+        if line.len() != width {
+            return Err(Error::parse(row + 1, 1, format!("expected a row of width {}", width)));
+        }
+        for (col, c) in line.chars().enumerate() {
             let value = match c {
                 '.' => DishObject::Empty,
                 '#' => DishObject::SquareRock,
                 'O' => DishObject::RoundRock,
-                _ => panic!("Unexpected input!"),
+                _ => return Err(Error::parse(row + 1, col + 1, format!("unexpected {:?}, expected '.', '#' or 'O'", c))),
             };
             data.push(value);
         }
     }
-    return Grid { data, width };
+    return Ok(Grid { data, width });
 }
 
 #[cfg(test)]
@@ -153,7 +185,7 @@ mod tests {
             ),
             width: 3,
         };
-        let actual = parse_input(input);
+        let actual = parse_input(input).unwrap();
         assert_eq!(expected, actual);
     }
 
@@ -231,8 +263,8 @@ mod tests {
             String::from("#....###.."),
             String::from("#....#...."),
         );
-        let expected_grid = parse_input(expected_str);
-        let mut grid = parse_input(input);
+        let expected_grid = parse_input(expected_str).unwrap();
+        let mut grid = parse_input(input).unwrap();
         grid.slide_rocks_north();
 
         assert_eq!(expected_grid, grid, "{}", grid);
@@ -251,7 +283,19 @@ mod tests {
             String::from("..O......."),
             String::from("#....###.."),
             String::from("#....#...."),
-        ));
+        )).unwrap();
         assert_eq!(136, grid.get_load());
     }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input(vec!(String::from("O.#"), String::from(".X."))).unwrap_err();
+        assert_eq!("line 2, column 2: unexpected 'X', expected '.', '#' or 'O'", err.to_string());
+
+        let err = parse_input(vec!(String::from("O.#"), String::from(".O"))).unwrap_err();
+        assert_eq!("line 2, column 1: expected a row of width 3", err.to_string());
+
+        let err = parse_input(vec!(String::new())).unwrap_err();
+        assert_eq!("line 1, column 1: expected a row of at least one tile", err.to_string());
+    }
 }
\ No newline at end of file
@@ -1,19 +1,50 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    for line in input {
-        let result: u32 = line.unwrap().split(",")
-            .map(hash)
-            .map(|x|u32::from(x))
-            .sum();
-        return result.to_string();
-    }
-    panic!("No input data!");
+
+use crate::error::{ Error, Result };
+use crate::solver::Solver;
+
+pub struct Day15;
+
+impl Solver for Day15 {
+    type Input = Vec<String>;
+
+    fn day(&self) -> u32 {
+        15
+    }
+
+    fn title(&self) -> &'static str {
+        "Lens Library"
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<String>> {
+        parse_input(input)
+    }
+
+    fn part1(&self, steps: &Vec<String>) -> Result<String> {
+        Ok(solve_part1(steps))
+    }
+
+    fn part2(&self, steps: &Vec<String>) -> Result<String> {
+        solve_part2(steps)
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<String>> {
+    match input.lines().next() {
+        Some(line) => Ok(line.split(",").map(String::from).collect()),
+        None => Err(Error::parse(1, 1, "no input data")),
+    }
+}
+
+pub(crate) fn solve_part1(steps: &[String])  -> String {
+    let result: u32 = steps.iter()
+        .map(|s| hash(s))
+        .map(u32::from)
+        .sum();
+    return result.to_string();
 }
 
-pub(crate) fn solve_part2(_input: Lines<BufReader<File>>)  -> String {
-    return String::from("TODO");
+pub(crate) fn solve_part2(_steps: &[String])  -> Result<String> {
+    return Err(Error::Unsolved);
 }
 
 fn hash(s: &str) -> u8 {
@@ -1,7 +1,7 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
 use std::fmt;
 
+use crate::error::{ Error, Result };
+use crate::solver::Solver;
 
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
 enum Dir {
@@ -53,7 +53,8 @@ impl Tile {
     }
 }
 
-struct Grid {
+#[derive(Clone)]
+pub struct Grid {
     tiles: Vec<Vec<Tile>>
 }
 
@@ -290,71 +291,102 @@ impl fmt::Display for Grid {
 }
 
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    let mut grid = parse_input(data);
-    let start = Beam{row: 0, col: 0, dir: Dir::East};
-    return simulate_beam(&mut grid, start).to_string();
-}
+pub struct Day16;
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    return part_2_solver(data).to_string();
-}
+impl Solver for Day16 {
+    type Input = Grid;
 
-fn simulate_beam(grid: &mut Grid, start: Beam) -> usize {
-    let mut beams = vec!(start);
+    fn day(&self) -> u32 {
+        16
+    }
 
-    loop {
-        match beams.pop() {
-            None => break,
-            Some(b) => {
-                let mut new_beams = grid.shoot_beam(b);
-                beams.append(&mut new_beams);
-            },
-        }
+    fn title(&self) -> &'static str {
+        "The Floor Will Be Lava"
     }
 
-    return grid.count_energized();
+    fn parse(&self, input: &str) -> Result<Grid> {
+        parse_input(input.lines().map(String::from).collect())
+    }
+
+    fn part1(&self, grid: &Grid) -> Result<String> {
+        Ok(solve_part1(grid))
+    }
+
+    fn part2(&self, grid: &Grid) -> Result<String> {
+        Ok(solve_part2(grid))
+    }
+}
+
+pub(crate) fn solve_part1(grid: &Grid)  -> String {
+    let mut grid = grid.clone();
+    let start = Beam{row: 0, col: 0, dir: Dir::East};
+    return simulate_beam(&mut grid, start).to_string();
 }
 
-fn part_2_solver(data: Vec<String>) -> usize {
-    let height = data.len();
-    let width = data.get(0).expect("no input data!").len();
+pub(crate) fn solve_part2(grid: &Grid)  -> String {
+    let height = grid.tiles.len();
+    let width = grid.tiles.first().expect("no input data!").len();
     let mut best: usize = 0;
 
     for row in 0..height {
-        let mut grid = parse_input(data.clone());
+        let mut energized = grid.clone();
         let start = Beam { row, col: 0, dir: Dir::East};
-        let result = simulate_beam(&mut grid, start);
+        let result = simulate_beam(&mut energized, start);
         best = best.max(result);
 
-        let mut grid = parse_input(data.clone());
+        let mut energized = grid.clone();
         let start = Beam{row, col: width-1, dir: Dir::West};
-        let result = simulate_beam(&mut grid, start);
+        let result = simulate_beam(&mut energized, start);
         best = best.max(result);
     }
     for col in 0..width {
-        let mut grid = parse_input(data.clone());
+        let mut energized = grid.clone();
         let start = Beam{row: 0, col, dir: Dir::South};
-        let result = simulate_beam(&mut grid, start);
+        let result = simulate_beam(&mut energized, start);
         best = best.max(result);
 
-        let mut grid = parse_input(data.clone());
+        let mut energized = grid.clone();
         let start = Beam{row: height-1, col, dir: Dir::North};
-        let result = simulate_beam(&mut grid, start);
+        let result = simulate_beam(&mut energized, start);
         best = best.max(result);
     }
-    return best;
+    return best.to_string();
+}
+
+fn simulate_beam(grid: &mut Grid, start: Beam) -> usize {
+    let mut beams = vec!(start);
+
+    loop {
+        match beams.pop() {
+            None => break,
+            Some(b) => {
+                let mut new_beams = grid.shoot_beam(b);
+                beams.append(&mut new_beams);
+            },
+        }
+    }
+
+    return grid.count_energized();
 }
 
-fn parse_input(input: Vec<String>) -> Grid {
+
+fn parse_input(input: Vec<String>) -> Result<Grid> {
+    let width = input.first().ok_or_else(|| Error::parse(1, 1, "no input data"))?.len();
+    if width == 0 {
+        return Err(Error::parse(1, 1, "expected a row of at least one tile"));
+    }
     let mut tiles: Vec<Vec<Tile>> = vec!();
     for (row, r) in input.iter().enumerate() {
+        if r.len() != width {
+            return Err(Error::parse(row + 1, 1, format!("expected a row of width {}", width)));
+        }
+        if let Some(col) = r.find(|c| !".|-/\\".contains(c)) {
+            return Err(Error::parse(row + 1, col + 1, "expected '.', '|', '-', '/' or '\\'"));
+        }
         let tile_row: Vec<Tile> = r.chars().enumerate().map(|(col, value)| Tile::new(row, col, value)).collect();
         tiles.push(tile_row);
     }
-    return Grid{tiles};
+    return Ok(Grid{tiles});
 }
 
 #[cfg(test)]
@@ -374,7 +406,7 @@ mod tests {
             String::from(".-.-/..|.."),
             String::from(".|....-|.\\"),
             String::from("..//.|...."),
-        ));
+        )).unwrap();
         let start = Beam{row: 0, col: 0, dir: Dir::East};
         let result = simulate_beam(&mut grid, start);
         assert_eq!(46, result);
@@ -382,20 +414,19 @@ mod tests {
 
     #[test]
     fn test_part_2_solver() {
-        let data = vec!(
-            String::from(".|...\\...."),
-            String::from("|.-.\\....."),
-            String::from(".....|-..."),
-            String::from("........|."),
-            String::from(".........."),
-            String::from(".........\\"),
-            String::from("..../.\\\\.."),
-            String::from(".-.-/..|.."),
-            String::from(".|....-|.\\"),
-            String::from("..//.|...."),
-        );
-        let result = part_2_solver(data);
-        assert_eq!(51, result);
+        let input = [
+            ".|...\\....",
+            "|.-.\\.....",
+            ".....|-...",
+            "........|.",
+            "..........",
+            ".........\\",
+            "..../.\\\\..",
+            ".-.-/..|..",
+            ".|....-|.\\",
+            "..//.|....",
+        ].map(String::from);
+        assert_eq!("51", solve_part2(&parse_input(input.to_vec()).unwrap()));
     }
 
     #[test]
@@ -425,7 +456,7 @@ mod tests {
                 Tile::new(2, 3, '/'),
             ),
         );
-        let actual = parse_input(input);
+        let actual = parse_input(input).unwrap();
         assert_eq!(expected, actual.tiles);
     }
 
@@ -436,7 +467,7 @@ mod tests {
             String::from("|.-."),
             String::from(".../"),
             String::from("...."),
-        ));
+        )).unwrap();
         assert_eq!(Option::None, grid.get_next_location(0, 0, Dir::North));
         assert_eq!(Option::None, grid.get_next_location(0, 0, Dir::West));
         assert_eq!(Option::Some((1, 0)), grid.get_next_location(0, 0, Dir::South));
@@ -452,7 +483,7 @@ mod tests {
     fn test_shoot_beam_straight_line() {
         let mut grid = parse_input(vec!(
             String::from("..\\."),
-        ));
+        )).unwrap();
         let beam = Beam {
             row: 0,
             col: 0,
@@ -473,7 +504,7 @@ mod tests {
             String::from("...."),
             String::from("/--/"),
             String::from("\\..."),
-        ));
+        )).unwrap();
         let beam = Beam {
             row: 0,
             col: 0,
@@ -509,7 +540,7 @@ mod tests {
             String::from(".|.."),
             String::from(".|.."),
             String::from(".-.."),
-        ));
+        )).unwrap();
         let beam = Beam {
             row: 1,
             col: 0,
@@ -537,4 +568,13 @@ mod tests {
         assert_eq!(Beam {row: 1,col: 1,dir: Dir::North}, *beams.get(0).unwrap());
         assert_eq!(Beam {row: 3,col: 1,dir: Dir::East}, *beams.get(1).unwrap());
     }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input(vec!(String::from(".|."), String::from(".x."))).err().unwrap();
+        assert_eq!("line 2, column 2: expected '.', '|', '-', '/' or '\\'", err.to_string());
+
+        let err = parse_input(vec!(String::new())).err().unwrap();
+        assert_eq!("line 1, column 1: expected a row of at least one tile", err.to_string());
+    }
 }
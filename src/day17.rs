@@ -1,15 +1,15 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{ Lines, BufReader };
-use std::u32;
 use std::collections::BinaryHeap;
 
+use crate::error::{ Error, Result };
+use crate::solver::Solver;
+
 const PART_1_STOPPING_DISTANCE: usize = 0;
 const PART_2_STOPPING_DISTANCE: usize = 4;
 
 #[derive(Debug, PartialEq)]
-struct Grid {
+pub struct Grid {
     grid: Vec<Vec<u32>>,
     height: usize,
     width: usize,
@@ -118,27 +118,60 @@ impl PartialOrd for State {
     }
 }
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>) -> String {
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    let grid = parse_input(data);
-    let result = dijkstra(&grid, part_1_neighbors_strategy, PART_1_STOPPING_DISTANCE);
-    result.to_string()
+pub struct Day17;
+
+impl Solver for Day17 {
+    type Input = Grid;
+
+    fn day(&self) -> u32 {
+        17
+    }
+
+    fn title(&self) -> &'static str {
+        "Clumsy Crucible"
+    }
+
+    fn parse(&self, input: &str) -> Result<Grid> {
+        parse_input(input.lines().map(String::from).collect())
+    }
+
+    fn part1(&self, grid: &Grid) -> Result<String> {
+        solve_part1(grid)
+    }
+
+    fn part2(&self, grid: &Grid) -> Result<String> {
+        solve_part2(grid)
+    }
+}
+
+pub(crate) fn solve_part1(grid: &Grid) -> Result<String> {
+    let result = dijkstra(grid, part_1_neighbors_strategy, PART_1_STOPPING_DISTANCE)
+        .ok_or_else(|| Error::solve("the crucible cannot reach the factory"))?;
+    Ok(result.to_string())
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    let grid = parse_input(data);
-    let result = dijkstra(&grid, part_2_neighbors_strategy, PART_2_STOPPING_DISTANCE);
-    result.to_string()
+pub(crate) fn solve_part2(grid: &Grid)  -> Result<String> {
+    let result = dijkstra(grid, part_2_neighbors_strategy, PART_2_STOPPING_DISTANCE)
+        .ok_or_else(|| Error::solve("the ultra crucible cannot reach the factory"))?;
+    Ok(result.to_string())
 }
 
-fn parse_input(data: Vec<String>) -> Grid {
-    let grid: Vec<Vec<u32>> = data.iter()
-        .map(|row| row.chars().map(|c| c.to_digit(10).unwrap()).collect())
-        .collect();
-    let height = grid.len();
-    let width = grid.get(0).unwrap().len();
-    Grid{grid, height, width}
+fn parse_input(data: Vec<String>) -> Result<Grid> {
+    let width = data.first().ok_or_else(|| Error::parse(1, 1, "no input data"))?.len();
+    if width == 0 {
+        return Err(Error::parse(1, 1, "expected a row of at least one tile"));
+    }
+    let mut grid: Vec<Vec<u32>> = vec!();
+    for (row, line) in data.iter().enumerate() {
+        if line.len() != width {
+            return Err(Error::parse(row + 1, 1, format!("expected a row of width {}", width)));
+        }
+        let heat_loss = line.chars().enumerate()
+            .map(|(col, c)| c.to_digit(10).ok_or_else(|| Error::parse(row + 1, col + 1, "expected a digit")))
+            .collect::<Result<_>>()?;
+        grid.push(heat_loss);
+    }
+    Ok(Grid::new(grid))
 }
 
 fn part_1_neighbors_strategy(loc: &Location, g: &Grid) -> Vec<Location> {
@@ -203,7 +236,7 @@ fn part_2_neighbors_strategy(loc: &Location, g: &Grid) -> Vec<Location> {
     neighbors
 }
 
-fn dijkstra(grid: &Grid, neighbors_strategy: fn(&Location, &Grid) -> Vec<Location>, stopping_distance: usize) -> u32 {
+fn dijkstra(grid: &Grid, neighbors_strategy: fn(&Location, &Grid) -> Vec<Location>, stopping_distance: usize) -> Option<u32> {
     let start_node = Location::new();
     let mut visited: HashMap<Location, u32> = HashMap::new();
     visited.insert(start_node.clone(), 0);
@@ -214,7 +247,7 @@ fn dijkstra(grid: &Grid, neighbors_strategy: fn(&Location, &Grid) -> Vec<Locatio
     while let Some(State{cost, loc: current}) = unvisited.pop() {
 
         if grid.is_goal(&current) && current.dist >= stopping_distance {
-            return cost;
+            return Some(cost);
         }
 
         if visited.get(&current).is_some_and(|v| *v < cost) {
@@ -237,7 +270,7 @@ fn dijkstra(grid: &Grid, neighbors_strategy: fn(&Location, &Grid) -> Vec<Locatio
             }
         }
     }
-    panic!("should never be here");
+    None
 }
 
 #[cfg(test)]
@@ -325,7 +358,7 @@ mod tests {
             vec!(1, 1, 1),
         ));
 
-        let result = dijkstra(&grid, part_1_neighbors_strategy, PART_1_STOPPING_DISTANCE);
+        let result = dijkstra(&grid, part_1_neighbors_strategy, PART_1_STOPPING_DISTANCE).unwrap();
 
         assert_eq!(4, result);
     }
@@ -342,7 +375,7 @@ mod tests {
         ));
 
         
-        let result = dijkstra(&grid, part_1_neighbors_strategy, PART_1_STOPPING_DISTANCE);
+        let result = dijkstra(&grid, part_1_neighbors_strategy, PART_1_STOPPING_DISTANCE).unwrap();
         assert_eq!(13, result);
     }
 
@@ -363,7 +396,7 @@ mod tests {
             vec!(2,5,4,6,5,4,8,8,8,7,7,3,5),
             vec!(4,3,2,2,6,7,4,6,5,5,5,3,3),
         ));
-        let result = dijkstra(&grid, part_1_neighbors_strategy, PART_1_STOPPING_DISTANCE);
+        let result = dijkstra(&grid, part_1_neighbors_strategy, PART_1_STOPPING_DISTANCE).unwrap();
         assert_eq!(102, result);
     }
 
@@ -385,7 +418,7 @@ mod tests {
             vec!(2,5,4,6,5,4,8,8,8,7,7,3,5),
             vec!(4,3,2,2,6,7,4,6,5,5,5,3,3),
         ));
-        let result = dijkstra(&grid, part_2_neighbors_strategy, PART_2_STOPPING_DISTANCE);
+        let result = dijkstra(&grid, part_2_neighbors_strategy, PART_2_STOPPING_DISTANCE).unwrap();
         assert_eq!(94, result);
     }
 
@@ -398,7 +431,16 @@ mod tests {
             vec!(9,9,9,9,9,9,9,9,9,9,9,1),
             vec!(9,9,9,9,9,9,9,9,9,9,9,1),            
         ));
-        let result = dijkstra(&grid, part_2_neighbors_strategy, PART_2_STOPPING_DISTANCE);
+        let result = dijkstra(&grid, part_2_neighbors_strategy, PART_2_STOPPING_DISTANCE).unwrap();
         assert_eq!(71, result);
     }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input(vec!(String::from("241"), String::from("3x5"))).err().unwrap();
+        assert_eq!("line 2, column 2: expected a digit", err.to_string());
+
+        let err = parse_input(vec!(String::new())).err().unwrap();
+        assert_eq!("line 1, column 1: expected a row of at least one tile", err.to_string());
+    }
 }
@@ -1,11 +1,11 @@
 use std::collections::VecDeque;
-use std::fs::File;
-use std::i32;
-use std::io::{ Lines, BufReader };
 use lazy_static::lazy_static;
 
 use regex::Regex;
 
+use crate::error::{ self, Error, Line, Result };
+use crate::solver::Solver;
+
 lazy_static! {
     pub static ref DIG_PLAN_LINE_RE: Regex = Regex::new(r"(.) (\d+) \(([#0-9a-zA-Z]+)\)").unwrap();
 }
@@ -17,57 +17,100 @@ struct Coord {
 }
 
 #[derive(Debug, PartialEq)]
-struct DigPlanLine {
+pub struct DigPlanLine {
     dir: char,
     dist: u32,
 }
 
 impl DigPlanLine {
-    fn from_part_1(line: &str) -> Self {
-        for (_, [dir, dist, _color]) in DIG_PLAN_LINE_RE
-            .captures_iter(line)
+    fn from_part_1(line: Line) -> Result<Self> {
+        if let Some((_, [dir, dist, _color])) = DIG_PLAN_LINE_RE
+            .captures(line.text)
             .map(|x| x.extract()) {
 
+                if !["R", "L", "U", "D"].contains(&dir) {
+                    return Err(line.error(dir, "expected a direction of R, L, U or D"));
+                }
                 let dir = dir.chars().next().unwrap();
-                let dist: u32 = dist.parse::<u32>().unwrap();
-                return DigPlanLine{dir, dist}
+                let dist: u32 = line.parse::<u32>(dist)?;
+                return Ok(DigPlanLine{dir, dist})
         }
-        panic!("Invalid dig plan line");
+        Err(line.error(line.text, "invalid dig plan line"))
     }
 
-    fn from_part_2 (line: &str) -> Self {
-        for (_, [_dir, _dist, color]) in DIG_PLAN_LINE_RE
-            .captures_iter(line)
+    fn from_part_2 (line: Line) -> Result<Self> {
+        if let Some((_, [_dir, _dist, color])) = DIG_PLAN_LINE_RE
+            .captures(line.text)
             .map(|x| x.extract()) {
 
+                if color.len() != 7 || !color.starts_with('#') {
+                    return Err(line.error(color, "expected a color like #70c710"));
+                }
+
                 let dir = match color.chars().last().unwrap() {
                     '0' => 'R',
                     '1' => 'D',
                     '2' => 'L',
                     '3' => 'U',
-                    _ => panic!("Invalid directional character"),
+                    _ => return Err(line.error(&color[6..], "invalid directional character")),
                 };
 
-                let dist = u32::from_str_radix(&color[1..6], 16).unwrap();
-                return DigPlanLine{dir, dist}
+                let dist = u32::from_str_radix(&color[1..6], 16)
+                    .map_err(|_| line.error(&color[1..6], "expected a hexadecimal distance"))?;
+                return Ok(DigPlanLine{dir, dist})
         }
-        panic!("Invalid dig plan line");
+        Err(line.error(line.text, "invalid dig plan line"))
     }
 }
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    part_1_solver(data).to_string()
+/// The dig plan, read the way each part of the puzzle reads it.
+pub struct DigPlan {
+    part_1: Vec<DigPlanLine>,
+    part_2: Vec<DigPlanLine>,
+}
+
+fn parse_input(input: &str) -> Result<DigPlan> {
+    let part_1: Vec<DigPlanLine> = error::lines(input).map(DigPlanLine::from_part_1).collect::<Result<_>>()?;
+    let part_2 = error::lines(input).map(DigPlanLine::from_part_2).collect::<Result<_>>()?;
+    if part_1.is_empty() {
+        return Err(Error::parse(1, 1, "no input data"));
+    }
+    Ok(DigPlan { part_1, part_2 })
+}
+
+pub struct Day18;
+
+impl Solver for Day18 {
+    type Input = DigPlan;
+
+    fn day(&self) -> u32 {
+        18
+    }
+
+    fn title(&self) -> &'static str {
+        "Lavaduct Lagoon"
+    }
+
+    fn parse(&self, input: &str) -> Result<DigPlan> {
+        parse_input(input)
+    }
+
+    fn part1(&self, plan: &DigPlan) -> Result<String> {
+        solve_part1(&plan.part_1)
+    }
+
+    fn part2(&self, plan: &DigPlan) -> Result<String> {
+        solve_part2(&plan.part_2)
+    }
 }
 
-fn part_1_solver(data: Vec<String>) -> usize {
-    let dig_plan: Vec<DigPlanLine> = data.iter().map(|line| DigPlanLine::from_part_1(line)).collect();
+pub(crate) fn solve_part1(dig_plan: &[DigPlanLine])  -> Result<String> {
 
-    // Dig out the trench
-    let mut min_x = i32::MAX;
-    let mut min_y = i32::MAX;
-    let mut max_x = i32::MIN;
-    let mut max_y = i32::MIN;
+    // Dig out the trench, starting from the origin
+    let mut min_x = 0;
+    let mut min_y = 0;
+    let mut max_x = 0;
+    let mut max_y = 0;
     let mut current_coord = Coord{x: 0, y: 0};
     let mut trench: Vec<Coord> = vec!(current_coord);
     for line in dig_plan {
@@ -76,7 +119,7 @@ fn part_1_solver(data: Vec<String>) -> usize {
             'L' => (-1, 0),
             'U' => (0, -1),
             'D' => (0, 1),
-            _ => panic!("Invalid direction in dig plan line")
+            _ => unreachable!("Directions are validated when the dig plan is parsed")
         };
         for _ in 0..line.dist {
             current_coord = Coord {
@@ -90,16 +133,14 @@ fn part_1_solver(data: Vec<String>) -> usize {
             trench.push(current_coord);
         }
     }
-    println!("Finished calculating trench. x range = ({}, {}) y range = ({}, {})", min_x, max_x, min_y, max_y);
+    if current_coord != (Coord{x: 0, y: 0}) {
+        return Err(Error::solve("the trench does not lead back to where it started"));
+    }
 
     // Figure out the width and height to translate everything so that 
     // the top left coordinate of the map is at (0, 0)
-    let width = usize::try_from(
-        min_x.abs_diff(max_x)
-    ).unwrap();
-    let height = usize::try_from(
-        min_y.abs_diff(max_y)
-    ).unwrap();
+    let width = min_x.abs_diff(max_x) as usize;
+    let height = min_y.abs_diff(max_y) as usize;
 
     // Build a 2D map of the dig
     let mut row: Vec<char> = vec!();
@@ -113,24 +154,23 @@ fn part_1_solver(data: Vec<String>) -> usize {
     }
 
     for coord in trench {
-        let x = coord.x - min_x;
-        let y = coord.y - min_y;
-        let x = usize::try_from(x).unwrap();
-        let y = usize::try_from(y).unwrap();
+        // the minimums include every coordinate, so these are never negative
+        let x = (coord.x - min_x) as usize;
+        let y = (coord.y - min_y) as usize;
         grid[y][x] = '#';
     }
 
     // Fill the inside of the trench
-    fill_grid(&mut grid, height, width);
+    fill_grid(&mut grid, height, width)?;
 
     // For debugging
     // print_grid(&grid);
 
     // Count the number of filled-in tiles
-    return grid.iter().flatten().filter(|v| **v != '.').count();
+    return Ok(grid.iter().flatten().filter(|v| **v != '.').count().to_string());
 }
 
-fn fill_grid(grid: &mut Vec<Vec<char>>, height: usize, _width: usize) {
+fn fill_grid(grid: &mut [Vec<char>], height: usize, width: usize) -> Result<()> {
     // We'll use BFS to fill the inside of the trench, but we need to find a point
     // inside the trench to start with. This way of finding it seems pretty reasonable,
     // although there could potentially be some edge cases such as a trench
@@ -143,27 +183,20 @@ fn fill_grid(grid: &mut Vec<Vec<char>>, height: usize, _width: usize) {
     // ...########
     // ...#......#
     // ...########
+    let outside = || Error::solve("could not find the inside of the trench");
     let start_y = height / 2;
-    let mut start_x = 0;
-    while grid[start_y][start_x] != '#' {
-        start_x += 1;
-    }
-    start_x += 1;
-
-    println!("Starting grid fill at ({}, {})", start_y, start_x);
+    let start_x = grid[start_y].iter().position(|&c| c == '#').ok_or_else(outside)? + 1;
 
     // bfs
     let mut queue = VecDeque::new();
-    queue.push_back(Coord{
-        x: start_x.try_into().unwrap(),
-        y: start_y.try_into().unwrap()
-    });
-
-    while !queue.is_empty() {
-        let current_coord = queue.pop_front().expect("Something went wrong.");
-        let x = usize::try_from(current_coord.x).unwrap();
-        let y = usize::try_from(current_coord.y).unwrap();
-        
+    queue.push_back((start_x, start_y));
+
+    while let Some((x, y)) = queue.pop_front() {
+        // a fill that reaches the edge of the map started outside the trench,
+        // or the trench has no inside at all
+        if x == 0 || y == 0 || x >= width || y >= height {
+            return Err(outside());
+        }
         if grid[y][x] == '#' {
             continue;
         }
@@ -172,38 +205,33 @@ fn fill_grid(grid: &mut Vec<Vec<char>>, height: usize, _width: usize) {
 
         // up
         if grid[y-1][x] != '#' {
-            queue.push_back(Coord{x: current_coord.x, y: current_coord.y-1});
+            queue.push_back((x, y-1));
         }
         // down
         if grid[y+1][x] != '#' {
-            queue.push_back(Coord{x: current_coord.x, y: current_coord.y+1});
+            queue.push_back((x, y+1));
         }
         // left
         if grid[y][x-1] != '#' {
-            queue.push_back(Coord{x: current_coord.x-1, y: current_coord.y});
+            queue.push_back((x-1, y));
         }
         // right
         if grid[y][x+1] != '#' {
-            queue.push_back(Coord{x: current_coord.x+1, y: current_coord.y});
+            queue.push_back((x+1, y));
         }
     }
-    println!("Finished filling trench");
-    
+    Ok(())
 }
 
 fn _print_grid(grid: &Vec<Vec<char>>) {
     for row in grid {
         let s: String = row.into_iter().collect();
-        println!("{}", s);
+        eprintln!("{}", s);
     }
 }
 
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
-    // for line in input {
-    //     println!("{}", line.unwrap());
-    // }
-
-    return String::from("");
+pub(crate) fn solve_part2(_dig_plan: &[DigPlanLine])  -> Result<String> {
+    return Err(Error::Unsolved);
 }
 
 #[cfg(test)]
@@ -218,30 +246,43 @@ mod tests {
         };
         let input = "R 6 (#70c710)";
         
-        let actual = DigPlanLine::from_part_1(input);
+        let actual = DigPlanLine::from_part_1(Line::new(1, input)).unwrap();
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn test_part_1_solver() {
-        let data = vec!(
-            String::from("R 6 (#70c710)"),
-            String::from("D 5 (#0dc571)"),
-            String::from("L 2 (#5713f0)"),
-            String::from("D 2 (#d2c081)"),
-            String::from("R 2 (#59c680)"),
-            String::from("D 2 (#411b91)"),
-            String::from("L 5 (#8ceee2)"),
-            String::from("U 2 (#caa173)"),
-            String::from("L 1 (#1b58a2)"),
-            String::from("U 2 (#caa171)"),
-            String::from("R 2 (#7807d2)"),
-            String::from("U 3 (#a77fa3)"),
-            String::from("L 2 (#015232)"),
-            String::from("U 2 (#7a21e3)"),
-        );
-        let volume = part_1_solver(data);
-        assert_eq!(62, volume);
+        let input = [
+            "R 6 (#70c710)",
+            "D 5 (#0dc571)",
+            "L 2 (#5713f0)",
+            "D 2 (#d2c081)",
+            "R 2 (#59c680)",
+            "D 2 (#411b91)",
+            "L 5 (#8ceee2)",
+            "U 2 (#caa173)",
+            "L 1 (#1b58a2)",
+            "U 2 (#caa171)",
+            "R 2 (#7807d2)",
+            "U 3 (#a77fa3)",
+            "L 2 (#015232)",
+            "U 2 (#7a21e3)",
+        ].join("\n");
+        let plan = parse_input(&input).unwrap();
+        assert_eq!("62", solve_part1(&plan.part_1).unwrap());
+    }
+
+    #[test]
+    fn test_part_1_errors() {
+        let plan = parse_input("R 6 (#70c710)").unwrap();
+        let err = solve_part1(&plan.part_1).unwrap_err();
+        assert_eq!("the trench does not lead back to where it started", err.to_string());
+
+        for input in ["U 0 (#000003)\n", "D 2 (#000021)\nU 2 (#000023)\n"] {
+            let plan = parse_input(input).unwrap();
+            let err = solve_part1(&plan.part_1).unwrap_err();
+            assert_eq!("could not find the inside of the trench", err.to_string(), "{}", input);
+        }
     }
 
     #[test]
@@ -252,7 +293,22 @@ mod tests {
         };
         let input = "R 6 (#70c710)";
         
-        let actual = DigPlanLine::from_part_2(input);
+        let actual = DigPlanLine::from_part_2(Line::new(1, input)).unwrap();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let err = parse_input("R 6 (#70c710)\nX 5 (#0dc571)\n").err().unwrap();
+        assert_eq!("line 2, column 1: expected a direction of R, L, U or D", err.to_string());
+
+        let err = parse_input("R 6 (#70c714)\n").err().unwrap();
+        assert_eq!("line 1, column 12: invalid directional character", err.to_string());
+
+        let err = parse_input("R six (#70c710)\n").err().unwrap();
+        assert_eq!("line 1, column 1: invalid dig plan line", err.to_string());
+
+        let err = parse_input("").err().unwrap();
+        assert_eq!("line 1, column 1: no input data", err.to_string());
+    }
 }
@@ -1,10 +1,11 @@
-use std::fs::File;
 use std::collections::HashMap;
-use std::io::{ Lines, BufReader };
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
+use crate::error::{ Error, Line, Result };
+use crate::solver::Solver;
+
 lazy_static! {
     // qs{s>3448:A,lnx}
     pub static ref RULE_SET_RE: Regex = Regex::new(r"([a-zA-Z]+)\{(.+)}").unwrap();
@@ -16,7 +17,7 @@ lazy_static! {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct MachinePart {
+pub struct MachinePart {
     x: i32,
     m: i32,
     a: i32,
@@ -123,7 +124,7 @@ impl Rule {
     }
 }
 
-struct RuleSet {
+pub struct RuleSet {
     rules: Vec<Rule>
 }
 
@@ -141,60 +142,85 @@ impl RuleSet {
 
 type RuleMap = HashMap<String, RuleSet>;
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    let data: Vec<String> = input.map(|x| x.unwrap()).collect();
-    let (rules, parts) = parse_input(data);
-    part_1_solver(rules, parts).to_string()
+pub struct Day19;
+
+impl Solver for Day19 {
+    type Input = (RuleMap, Vec<MachinePart>);
+
+    fn day(&self) -> u32 {
+        19
+    }
+
+    fn title(&self) -> &'static str {
+        "Aplenty"
+    }
+
+    fn parse(&self, input: &str) -> Result<(RuleMap, Vec<MachinePart>)> {
+        parse_input(input.lines().map(String::from).collect())
+    }
+
+    fn part1(&self, (rules, parts): &(RuleMap, Vec<MachinePart>)) -> Result<String> {
+        solve_part1(rules, parts)
+    }
+
+    fn part2(&self, (rules, parts): &(RuleMap, Vec<MachinePart>)) -> Result<String> {
+        solve_part2(rules, parts)
+    }
 }
 
-pub(crate) fn solve_part2(_input: Lines<BufReader<File>>)  -> String {
-    return String::from("TODO");
+pub(crate) fn solve_part1(rules: &RuleMap, parts: &[MachinePart])  -> Result<String> {
+    Ok(part_1_solver(rules, parts)?.to_string())
 }
 
-fn parse_input(data: Vec<String>) -> (RuleMap, Vec<MachinePart>) {
+pub(crate) fn solve_part2(_rules: &RuleMap, _parts: &[MachinePart])  -> Result<String> {
+    return Err(Error::Unsolved);
+}
+
+fn parse_input(data: Vec<String>) -> Result<(RuleMap, Vec<MachinePart>)> {
     let mut rules: HashMap<String, RuleSet> = HashMap::new();
     let mut parts: Vec<MachinePart> = vec!();
 
     let mut handling_rules = true;
 
-    for line in data {
-        if line.len() == 0 {
+    for (i, text) in data.iter().enumerate() {
+        let line = Line::new(i + 1, text);
+        if text.is_empty() {
             handling_rules = false;
             continue;
         }
         if handling_rules {
-            for (_, [name, content]) in RULE_SET_RE
-                .captures_iter(&line)
-                .map(|x| x.extract()) {
-                    let rule_set = parse_ruleset(content);
-                    rules.insert(name.to_string(), rule_set);
-                }
+            let (_, [name, content]) = RULE_SET_RE
+                .captures(text)
+                .map(|x| x.extract())
+                .ok_or_else(|| line.error(text, "expected a workflow like \"px{a<2006:qkq,rfg}\""))?;
+            let rule_set = parse_ruleset(content, line)?;
+            rules.insert(name.to_string(), rule_set);
 
         } else {
-            for (_, [x, m, a, s]) in MACHINE_PART_RE
-                .captures_iter(&line)
-                .map(|z| z.extract()) {
-                    let x = x.parse::<i32>().unwrap();
-                    let m = m.parse::<i32>().unwrap();
-                    let a = a.parse::<i32>().unwrap();
-                    let s = s.parse::<i32>().unwrap();
-                    parts.push(MachinePart{x, m, a, s});
-                }
+            let (_, [x, m, a, s]) = MACHINE_PART_RE
+                .captures(text)
+                .map(|z| z.extract())
+                .ok_or_else(|| line.error(text, "expected a part like \"{x=787,m=2655,a=1222,s=2876}\""))?;
+            let x = line.parse::<i32>(x)?;
+            let m = line.parse::<i32>(m)?;
+            let a = line.parse::<i32>(a)?;
+            let s = line.parse::<i32>(s)?;
+            parts.push(MachinePart{x, m, a, s});
         }
     }
-    return (rules, parts);
+    return Ok((rules, parts));
 }
 
-fn parse_ruleset(rule: &str) -> RuleSet {
+fn parse_ruleset(rule: &str, line: Line) -> Result<RuleSet> {
     let rules: Vec<Rule> = rule.split(",").map(|txt| {
         if txt == "A" {
-            return Rule::new_accept();
+            return Ok(Rule::new_accept());
         }
         if txt == "R" {
-            return Rule::new_reject();
+            return Ok(Rule::new_reject());
         }
         if !txt.contains(":") {
-            return Rule::new_route(txt);
+            return Ok(Rule::new_route(txt));
         }
 
         for (_, [left, operator, right, if_true]) in RULE_RE
@@ -208,28 +234,30 @@ fn parse_ruleset(rule: &str) -> RuleSet {
                 let cond = Condition{
                     left_operand: left.chars().next().unwrap(),
                     operator,
-                    right_operand: right.parse::<i32>().expect("Not an i32??"),
+                    right_operand: line.parse::<i32>(right)?,
                     if_true: if_true.to_string(),
                 };
-                return Rule::new_condition(cond);
+                return Ok(Rule::new_condition(cond));
             }
-        panic!("Rule does not conform to expected formats!");
+        Err(line.error(txt, "rule does not conform to expected formats"))
 
-    }).collect();
+    }).collect::<Result<_>>()?;
 
-    RuleSet{rules}
+    Ok(RuleSet{rules})
 }
 
-fn part_1_solver(rules: RuleMap, parts: Vec<MachinePart>) -> i32 {
+fn part_1_solver(rules: &RuleMap, parts: &[MachinePart]) -> Result<i32> {
     let a = "A".to_string();
     let r = "R".to_string();
     let mut accepted = 0;
 
     for part in parts {
-        let mut ruleset = rules.get("in").unwrap();
+        let mut name = String::from("in");
         loop {
-            let result = ruleset.evaluate(&part);
-            let val = result.expect("None result is unexpected.");
+            let ruleset = rules.get(&name)
+                .ok_or_else(|| Error::solve(format!("there is no workflow named {}", name)))?;
+            let val = ruleset.evaluate(part)
+                .ok_or_else(|| Error::solve(format!("workflow {} does not decide where {:?} goes", name, part)))?;
             if a == val {
                 accepted += part.sum();
                 break;
@@ -237,11 +265,11 @@ fn part_1_solver(rules: RuleMap, parts: Vec<MachinePart>) -> i32 {
             if r == val {
                 break;
             }
-            ruleset = rules.get(&val).expect("Expected routing to always lead to real rule");
+            name = val;
         }
     }
 
-    return accepted
+    return Ok(accepted)
 }
 
 #[cfg(test)]
@@ -269,7 +297,7 @@ mod tests {
             String::from("{x=2461,m=1339,a=466,s=291}"),
             String::from("{x=2127,m=1623,a=2188,s=1013}"),
         );
-        let (rule_map, parts) = parse_input(data);
+        let (rule_map, parts) = parse_input(data).unwrap();
         assert_eq!(true, rule_map.contains_key("hdj"));
         assert_eq!(5, parts.len());
         let expected = MachinePart{x:2127, m: 1623, a:2188, s: 1013};
@@ -279,7 +307,7 @@ mod tests {
     #[test]
     fn test_parse_ruleset() {
         let txt = "a<2006:qkq,m>2090:A,rfg";
-        let rule_set = parse_ruleset(txt);
+        let rule_set = parse_ruleset(txt, Line::new(1, txt)).unwrap();
         assert_eq!(3, rule_set.rules.len());
 
         for (i, e)  in rule_set.rules.iter().enumerate() {
@@ -338,7 +366,7 @@ mod tests {
     #[test]
     fn test_evaluate_ruleset() {
         let txt = "a<2006:qkq,m>2090:A,rfg";
-        let rule_set = parse_ruleset(txt);
+        let rule_set = parse_ruleset(txt, Line::new(1, txt)).unwrap();
 
         let route = MachinePart{x:1, m: 1, a: 2005, s: 1};
         let route_result = rule_set.evaluate(&route);
@@ -377,8 +405,38 @@ mod tests {
             String::from("{x=2461,m=1339,a=466,s=291}"),
             String::from("{x=2127,m=1623,a=2188,s=1013}"),
         );
-        let (rules, parts) = parse_input(data);
-        let result = part_1_solver(rules, parts);
+        let (rules, parts) = parse_input(data).unwrap();
+        let result = part_1_solver(&rules, &parts).unwrap();
         assert_eq!(19114, result);
     }
+
+    #[test]
+    fn test_parse_input_errors() {
+        let data = vec!(
+            String::from("px{a<2006:qkq,m>2090:A,rfg}"),
+            String::from("pv{a=1716:R,A}"),
+        );
+        let err = parse_input(data).err().unwrap();
+        assert_eq!("line 2, column 4: rule does not conform to expected formats", err.to_string());
+
+        let data = vec!(
+            String::from("in{A}"),
+            String::from(""),
+            String::from("{x=787,m=2655,a=12z2,s=2876}"),
+        );
+        let err = parse_input(data).err().unwrap();
+        assert_eq!("line 3, column 17: expected a number, found \"12z2\"", err.to_string());
+    }
+
+    #[test]
+    fn test_part_1_solver_unknown_workflow() {
+        let data = vec!(
+            String::from("in{s<1351:px,A}"),
+            String::from(""),
+            String::from("{x=787,m=2655,a=1222,s=876}"),
+        );
+        let (rules, parts) = parse_input(data).unwrap();
+        let err = part_1_solver(&rules, &parts).unwrap_err();
+        assert_eq!("there is no workflow named px", err.to_string());
+    }
 }
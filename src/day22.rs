@@ -1,17 +1,36 @@
-use std::fs::File;
-use std::io::{ Lines, BufReader };
 
-pub(crate) fn solve_part1(input: Lines<BufReader<File>>)  -> String {
-    for line in input {
-        println!("{}", line.unwrap());
+use crate::error::{ Error, Result };
+use crate::solver::Solver;
+
+pub struct Day22;
+
+impl Solver for Day22 {
+    type Input = Vec<String>;
+
+    fn day(&self) -> u32 {
+        22
     }
 
-return String::from("");
-}
-pub(crate) fn solve_part2(input: Lines<BufReader<File>>)  -> String {
-    for line in input {
-        println!("{}", line.unwrap());
+    fn title(&self) -> &'static str {
+        "Sand Slabs"
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<String>> {
+        Ok(input.lines().map(String::from).collect())
     }
 
-return String::from("");
+    fn part1(&self, lines: &Vec<String>) -> Result<String> {
+        solve_part1(lines)
+    }
+
+    fn part2(&self, lines: &Vec<String>) -> Result<String> {
+        solve_part2(lines)
+    }
+}
+
+pub(crate) fn solve_part1(_lines: &[String])  -> Result<String> {
+    return Err(Error::Unsolved);
+}
+pub(crate) fn solve_part2(_lines: &[String])  -> Result<String> {
+    return Err(Error::Unsolved);
 }
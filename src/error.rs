@@ -0,0 +1,136 @@
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// Everything that can go wrong while reading, parsing or solving a puzzle.
+#[derive(Debug)]
+pub enum Error {
+    /// The puzzle input is malformed. `line` and `column` are 1-based and
+    /// point at the offending text.
+    Parse { line: usize, column: usize, message: String },
+
+    /// The input parsed fine, but the puzzle cannot be solved with it.
+    Solve(String),
+
+    /// This part of the puzzle has not been solved yet.
+    Unsolved,
+
+    /// The puzzle input could not be read.
+    Io { path: String, source: io::Error },
+
+    /// There is no solver registered for the requested day.
+    UnknownDay(i64),
+
+    /// The command line arguments do not make sense.
+    Usage(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn parse(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Error::Parse { line, column, message: message.into() }
+    }
+
+    pub fn solve(message: impl Into<String>) -> Self {
+        Error::Solve(message.into())
+    }
+
+    /// Render the error for a human. Parse errors quote the offending line of
+    /// `input` and mark the column they refer to.
+    pub fn diagnostic(&self, input: &str) -> String {
+        match self {
+            Error::Parse { line, column, .. } => {
+                let text = input.lines().nth(line - 1).unwrap_or("");
+                let gutter = line.to_string().len();
+                format!("{}\n{:>w$} |\n{} | {}\n{:>w$} | {:>c$}",
+                    self, "", line, text, "", "^", w = gutter, c = *column)
+            },
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse { line, column, message } => write!(f, "line {}, column {}: {}", line, column, message),
+            Error::Solve(message) => write!(f, "{}", message),
+            Error::Unsolved => write!(f, "this part has not been solved yet"),
+            Error::Io { path, source } => write!(f, "{}: {}", path, source),
+            Error::UnknownDay(day) => write!(f, "there is no solution for day {}", day),
+            Error::Usage(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// A line of puzzle input together with its (1-based) line number, so that
+/// parsers can point at the exact place where the input went wrong.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn new(number: usize, text: &'a str) -> Self {
+        Line { number, text }
+    }
+
+    /// An error about `part`, which must be a slice of this line.
+    pub fn error(&self, part: &str, message: impl Into<String>) -> Error {
+        let offset = (part.as_ptr() as usize).saturating_sub(self.text.as_ptr() as usize);
+        Error::parse(self.number, offset.min(self.text.len()) + 1, message)
+    }
+
+    /// Parse `part`, a slice of this line, as a number.
+    pub fn parse<T: FromStr>(&self, part: &str) -> Result<T> {
+        part.trim().parse().map_err(|_| self.error(part, format!("expected a number, found {:?}", part)))
+    }
+}
+
+/// Iterate over the lines of `input`, numbered from 1.
+pub(crate) fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input.lines().enumerate().map(|(i, text)| Line::new(i + 1, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() {
+        let line = Line::new(3, "Time: 7 15 x");
+        assert_eq!(15, line.parse::<u32>(&line.text[8..10]).unwrap());
+
+        let err = line.parse::<u32>(&line.text[11..]).unwrap_err();
+        assert_eq!("line 3, column 12: expected a number, found \"x\"", err.to_string());
+    }
+
+    #[test]
+    fn test_lines() {
+        let numbered: Vec<(usize, &str)> = lines("a\nb\n").map(|l| (l.number, l.text)).collect();
+        assert_eq!(vec!((1, "a"), (2, "b")), numbered);
+    }
+
+    #[test]
+    fn test_diagnostic() {
+        let err = Error::parse(2, 3, "not a valid card");
+        let expected = [
+            "line 2, column 3: not a valid card",
+            "  |",
+            "2 | 32X3K 765",
+            "  |   ^",
+        ].join("\n");
+        assert_eq!(expected, err.diagnostic("AAAAA 1\n32X3K 765\n"));
+    }
+}
@@ -1,117 +1,207 @@
 extern crate lapp;
 use std::fs::File;
-use std::io::{ self, BufRead, BufReader };
-use std::time::Instant;
-
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
+use std::io::{ self, BufReader };
+use std::path::PathBuf;
+use std::process;
+
+use advent_of_code_2023::answers::{ self, Answers };
+use advent_of_code_2023::error::{ Error, Result };
+use advent_of_code_2023::runner::{ self, DayRun, Format, Summary, Timing };
+use advent_of_code_2023::solver::{ self, DynSolver, Settings };
 
 fn main() {
 
     let args = lapp::parse_args("
 Run solutions to Advent Of Code 2023.
-  <day> (integer) Which day's challenge to solve
-  <file> (string) The input file to use
+  -t, --text (default '') Puzzle input given inline, instead of reading <file>
+  -i, --inputs (default 'inputs') Directory of dayNN.txt inputs, used when running several days
+  -v, --verify Check the answers against a file of known answers, failing if any of them changed
+  -a, --answers (default '') The file of known answers for --verify, instead of <inputs>/answers.toml
+  -r, --repeat (default 1) Run each step this many times, and report the spread of the timings
+  -f, --format (default 'text') How to print the results: text, or csv or json for use by other programs
+  -e, --explain After solving a single day, explain how the answers came about, where the day supports it. With --format csv, print only the explanation as CSV
+  --cubes (default '') Day 2: the cubes in the bag, such as red=12,green=13,blue=14
+  <day> (string) Which day's challenge to solve, a range of days such as 1-14, or all
+  <file> (default '-') The input file to use, or - to read from stdin
     ");
 
-    let d = args.get_integer("day");
+    let d = args.get_string("day");
     let f = args.get_string("file");
-    println!("Solving day {} with input {}", d, f);
-
-    let input = read_lines(&f);
-
-    let start = Instant::now();
-    let part1 = match d {
-        1 => day01::solve_part1(input),
-        2 => day02::solve_part1(input),
-        3 => day03::solve_part1(input),
-        4 => day04::solve_part1(input),
-        5 => day05::solve_part1(input),
-        6 => day06::solve_part1(input),
-        7 => day07::solve_part1(input),
-        8 => day08::solve_part1(input),
-        9 => day09::solve_part1(input),
-        10 => day10::solve_part1(input),
-        11 => day11::solve_part1(input),
-        12 => day12::solve_part1(input),
-        13 => day13::solve_part1(input),
-        14 => day14::solve_part1(input),
-        15 => day15::solve_part1(input),
-        16 => day16::solve_part1(input),
-        17 => day17::solve_part1(input),
-        18 => day18::solve_part1(input),
-        19 => day19::solve_part1(input),
-        20 => day20::solve_part1(input),
-        21 => day21::solve_part1(input),
-        22 => day22::solve_part1(input),
-        23 => day23::solve_part1(input),
-        24 => day24::solve_part1(input),
-        25 => day25::solve_part1(input),
-        i32::MIN..=0_i32 | 2_i32..=i32::MAX => panic!("Invalid input!"),
-    };
-    println!("Solved Part 1 in {} ms. Answer: {}", start.elapsed().as_millis(), part1);
-
-    let input = read_lines(&f);
-
-    let start = Instant::now();
-    let part2 = match d {
-        1 => day01::solve_part2(input),
-        2 => day02::solve_part2(input),
-        3 => day03::solve_part2(input),
-        4 => day04::solve_part2(input),
-        5 => day05::solve_part2(input),
-        6 => day06::solve_part2(input),
-        7 => day07::solve_part2(input),
-        8 => day08::solve_part2(input),
-        9 => day09::solve_part2(input),
-        10 => day10::solve_part2(input),
-        11 => day11::solve_part2(input),
-        12 => day12::solve_part2(input),
-        13 => day13::solve_part2(input),
-        14 => day14::solve_part2(input),
-        15 => day15::solve_part2(input),
-        16 => day16::solve_part2(input),
-        17 => day17::solve_part2(input),
-        18 => day18::solve_part2(input),
-        19 => day19::solve_part2(input),
-        20 => day20::solve_part2(input),
-        21 => day21::solve_part2(input),
-        22 => day22::solve_part2(input),
-        23 => day23::solve_part2(input),
-        24 => day24::solve_part2(input),
-        25 => day25::solve_part2(input),
-        i32::MIN..=0_i32 | 2_i32..=i32::MAX => panic!("Invalid input!"),
-    };
-    println!("Solved Part 2 in {} ms. Answer: {}", start.elapsed().as_millis(), part2)
+    let text = args.get_string("text");
+    let inputs = args.get_string("inputs");
+    let verify = args.get_bool("verify");
+    let answers = args.get_string("answers");
+    let repeat = args.get_integer("repeat");
+    let format = args.get_string("format");
+    let explain = args.get_bool("explain");
+    let cubes = args.get_string("cubes");
+
+    // Parse errors quote the input, so hold on to it until we know how it went.
+    let mut input = String::new();
+    let result = Options::new(inputs, repeat, &format, &cubes).and_then(|options| {
+        if verify {
+            run_verify(&d, &answers, &mut input, &options)
+        } else if d == "all" || d.contains('-') {
+            run_all(&d, &options)
+        } else {
+            run(&d, &f, text, &mut input, &options, explain)
+        }
+    });
+
+    if let Err(err) = result {
+        eprintln!("error: {}", err.diagnostic(&input));
+        process::exit(1);
+    }
+}
+
+/// Command line settings that apply to whichever days are run.
+struct Options {
+    inputs: PathBuf,
+    repeat: u32,
+    format: Format,
+    settings: Settings,
+}
+
+impl Options {
+    fn new(inputs: String, repeat: i32, format: &str, cubes: &str) -> Result<Self> {
+        let format = format.parse::<Format>()?;
+        let repeat = u32::try_from(repeat).ok()
+            .filter(|&repeat| repeat > 0)
+            .ok_or_else(|| Error::Usage(format!("expected --repeat to be at least 1, found {}", repeat)))?;
+
+        let mut settings = Settings::default();
+        if !cubes.is_empty() {
+            settings.set("cubes", cubes);
+        }
+        // report a bad setting straight away, whichever days end up running
+        for solver in solver::SOLVERS {
+            solver.configure(&settings)?;
+        }
+
+        Ok(Options { inputs: PathBuf::from(inputs), repeat, format, settings })
+    }
 }
 
+fn run(d: &str, f: &str, text: String, input: &mut String, options: &Options, explain: bool) -> Result<()> {
+    let solver = select_day(d)?;
+    let configured = solver.configure(&options.settings)?;
+    let solver = configured.as_deref().unwrap_or(solver);
+    let (repeat, format) = (options.repeat, options.format);
+
+    let inline = !text.is_empty();
+    *input = if inline { text } else { read_input(f)? };
+
+    if explain && format != Format::Text {
+        // the explanation is the whole report, in place of the answers
+        let parsed = solver.parse(input)?;
+        let explanation = solver.explain(&parsed, format)
+            .ok_or_else(|| Error::Usage(format!("day {} cannot explain its answers as {}", solver.day(), format.name())))?;
+        print!("{}", explanation);
+        return Ok(());
+    }
+
+    if format != Format::Text {
+        let (parse_time, parts) = runner::solve(solver, input, repeat);
+        let path = if inline { "(inline)" } else { f };
+        let day = DayRun { day: solver.day(), title: solver.title(), input: PathBuf::from(path), parse_time, parts };
+        let summary = Summary { days: vec!(day) };
+        print!("{}", summary.render(format));
+        if summary.has_failures() {
+            return Err(Error::solve("the day failed"));
+        }
+        return Ok(());
+    }
+
+    if inline {
+        println!("Solving day {} ({}) with inline input", d, solver.title());
+    } else {
+        println!("Solving day {} ({}) with input {}", d, solver.title(), f);
+    }
+
+    let (parsed, time) = runner::measure(repeat, || solver.parse(input));
+    let parsed = parsed?;
+    println!("Parsed input in {}", time);
+
+    let (part1, time) = runner::measure(repeat, || solver.part1(&parsed));
+    print_answer(1, time, part1)?;
+
+    let (part2, time) = runner::measure(repeat, || solver.part2(&parsed));
+    print_answer(2, time, part2)?;
+
+    if explain {
+        match solver.explain(&parsed, Format::Text) {
+            Some(explanation) => print!("\n{}", explanation),
+            None => println!("\nDay {} has nothing to explain", solver.day()),
+        }
+    }
+    Ok(())
+}
+
+fn select_day(d: &str) -> Result<&'static dyn DynSolver> {
+    let day = d.parse::<i64>()
+        .map_err(|_| Error::Usage(format!("expected a day, a range such as 1-14, or \"all\", found {:?}", d)))?;
+    u32::try_from(day).ok()
+        .and_then(solver::find)
+        .ok_or(Error::UnknownDay(day))
+}
+
+fn print_answer(part: u32, time: Timing, answer: Result<String>) -> Result<()> {
+    match answer {
+        Ok(answer) => println!("Solved Part {} in {}. Answer: {}", part, time, answer),
+        Err(Error::Unsolved) => println!("Part {} has not been solved yet", part),
+        Err(err) => return Err(err),
+    }
+    Ok(())
+}
+
+fn run_days(days: &str, options: &Options) -> Result<Summary> {
+    let days = runner::select_days(days)?
+        .into_iter()
+        .map(|solver| {
+            let configured = solver.configure(&options.settings)?;
+            Ok(runner::run_day(configured.as_deref().unwrap_or(solver), &options.inputs, options.repeat))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Summary { days })
+}
+
+fn run_all(days: &str, options: &Options) -> Result<()> {
+    let summary = run_days(days, options)?;
+    print!("{}", summary.render(options.format));
+
+    if summary.has_failures() {
+        return Err(Error::solve("some days failed"));
+    }
+    Ok(())
+}
+
+fn run_verify(days: &str, answers: &str, input: &mut String, options: &Options) -> Result<()> {
+    let answers = if answers.is_empty() { options.inputs.join("answers.toml") } else { PathBuf::from(answers) };
+    // errors in the answers file are shown against its text
+    *input = read_input(&answers.display().to_string())?;
+    let answers = Answers::parse(input)?;
+
+    let summary = run_days(days, options)?;
+    let verification = answers::verify(&summary, &answers);
+    println!("{}", verification);
+
+    if verification.regressions() > 0 {
+        return Err(Error::solve(format!("{} part(s) no longer match their recorded answer", verification.regressions())));
+    }
+    if verification.unchecked() > 0 {
+        return Err(Error::solve(format!("{} part(s) with a recorded answer could not be checked", verification.unchecked())));
+    }
+    Ok(())
+}
+
+
+fn read_input(filename: &str) -> Result<String> {
+    let io_error = |source| Error::Io { path: filename.to_string(), source };
 
-fn read_lines(filename: &String) -> io::Lines<BufReader<File>> {
+    if filename == "-" {
+        return solver::read_input(io::stdin().lock()).map_err(io_error);
+    }
     // Open the file in read-only mode.
-    let file = File::open(filename).unwrap(); 
-    // Read the file line by line, and return an iterator of the lines of the file.
-    return io::BufReader::new(file).lines(); 
+    let file = File::open(filename).map_err(io_error)?;
+    return solver::read_input(BufReader::new(file)).map_err(io_error);
 }
@@ -0,0 +1,517 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{ self, BufReader };
+use std::path::{ Path, PathBuf };
+use std::str::FromStr;
+use std::time::{ Duration, Instant };
+
+use serde_json::{ json, Value };
+
+use crate::error::{ Error, Result };
+use crate::solver::{ self, DynSolver, SOLVERS };
+
+/// How the results of a run are printed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// A table for humans.
+    Text,
+    Csv,
+    Json,
+}
+
+impl Format {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Text => "text",
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Format::Text),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(Error::Usage(format!("expected a format of text, csv or json, found {:?}", s))),
+        }
+    }
+}
+
+/// How running one part of a puzzle went.
+#[derive(Debug, PartialEq)]
+pub enum Status {
+    Solved(String),
+    /// The part was not run, e.g. because its input is missing or it has
+    /// not been solved yet.
+    Skipped(String),
+    Failed(String),
+}
+
+impl From<Result<String>> for Status {
+    fn from(result: Result<String>) -> Self {
+        match result {
+            Ok(answer) => Status::Solved(answer),
+            Err(Error::Unsolved) => Status::Skipped("not solved yet".to_string()),
+            Err(err) => Status::Failed(err.to_string()),
+        }
+    }
+}
+
+/// How long a step took, over one or more runs of it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Timing {
+    /// How many times the step was run; 0 if it never was.
+    pub runs: u32,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Timing::default();
+        }
+        samples.sort();
+
+        let runs = samples.len() as u32;
+        let mid = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) { (samples[mid - 1] + samples[mid]) / 2 } else { samples[mid] };
+        let mean = samples.iter().sum::<Duration>() / runs;
+
+        Timing { runs, min: samples[0], median, mean, max: samples[samples.len() - 1] }
+    }
+}
+
+impl fmt::Display for Timing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.runs <= 1 {
+            return write!(f, "{}", format_duration(self.mean));
+        }
+        write!(f, "{} (median of {} runs: min {}, mean {}, max {})",
+            format_duration(self.median), self.runs,
+            format_duration(self.min), format_duration(self.mean), format_duration(self.max))
+    }
+}
+
+/// Run `f` `runs` times (at least once), returning the last result and how
+/// long the runs took.
+pub fn measure<T>(runs: u32, mut f: impl FnMut() -> T) -> (T, Timing) {
+    let mut samples = Vec::with_capacity(runs as usize);
+    let mut result = None;
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        result = Some(f());
+        samples.push(start.elapsed());
+    }
+    (result.unwrap(), Timing::from_samples(samples))
+}
+
+/// Format a duration in whichever of ns, µs, ms or s keeps it readable.
+pub fn format_duration(d: Duration) -> String {
+    let nanos = d.as_nanos();
+    if nanos < 1_000 {
+        format!("{} ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.3} µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.3} ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.3} s", nanos as f64 / 1e9)
+    }
+}
+
+impl Status {
+    /// The name of the status in machine-readable output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Solved(_) => "solved",
+            Status::Skipped(_) => "skipped",
+            Status::Failed(_) => "failed",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PartRun {
+    pub part: u32,
+    pub status: Status,
+    pub time: Timing,
+}
+
+/// The outcome of parsing one day's input and solving both of its parts.
+#[derive(Debug)]
+pub struct DayRun {
+    pub day: u32,
+    pub title: &'static str,
+    pub input: PathBuf,
+    pub parse_time: Timing,
+    pub parts: Vec<PartRun>,
+}
+
+/// Pick the solvers for `spec`, which is either a single day (`7`), an
+/// inclusive range of days (`1-14`) or `all`.
+pub fn select_days(spec: &str) -> Result<Vec<&'static dyn DynSolver>> {
+    if spec == "all" {
+        return Ok(SOLVERS.to_vec());
+    }
+
+    let parse_day = |s: &str| s.trim().parse::<u32>()
+        .map_err(|_| Error::Usage(format!("expected a day, a range such as 1-14, or \"all\", found {:?}", spec)));
+
+    let (first, last) = match spec.split_once('-') {
+        Some((first, last)) => (parse_day(first)?, parse_day(last)?),
+        None => {
+            let day = parse_day(spec)?;
+            (day, day)
+        },
+    };
+    if first > last {
+        return Err(Error::Usage(format!("the range {:?} ends before it starts; did you mean {}-{}?", spec, last, first)));
+    }
+
+    (first..=last)
+        .map(|day| solver::find(day).ok_or(Error::UnknownDay(day.into())))
+        .collect()
+}
+
+/// Where the input for `day` is expected to live, e.g. `inputs/day03.txt`.
+pub fn input_path(dir: &Path, day: u32) -> PathBuf {
+    dir.join(format!("day{:02}.txt", day))
+}
+
+/// Parse `input` and solve both parts, running each step `repeat` times
+/// to time it. A parse failure is reported against both parts.
+pub fn solve(solver: &dyn DynSolver, input: &str, repeat: u32) -> (Timing, Vec<PartRun>) {
+    let (parsed, parse_time) = measure(repeat, || solver.parse(input));
+
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            let parts = (1..=2)
+                .map(|part| PartRun { part, status: Status::Failed(err.to_string()), time: Timing::default() })
+                .collect();
+            return (parse_time, parts);
+        },
+    };
+
+    let parts = (1..=2).map(|part| {
+        let (result, time) = measure(repeat, || if part == 1 { solver.part1(&parsed) } else { solver.part2(&parsed) });
+        let time = if result.is_ok() { time } else { Timing::default() };
+        PartRun { part, status: Status::from(result), time }
+    }).collect();
+
+    (parse_time, parts)
+}
+
+/// Run one day against its input in `input_dir`, `repeat` times. A missing
+/// input file skips the day instead of failing it.
+pub fn run_day(solver: &dyn DynSolver, input_dir: &Path, repeat: u32) -> DayRun {
+    let day = solver.day();
+    let input = input_path(input_dir, day);
+
+    let (parse_time, parts) = match read_file(&input) {
+        Ok(text) => solve(solver, &text, repeat),
+        Err(err) => {
+            let status = |_| match &err {
+                Error::Io { source, .. } if source.kind() == io::ErrorKind::NotFound =>
+                    Status::Skipped(format!("missing input {}", input.display())),
+                _ => Status::Failed(err.to_string()),
+            };
+            let parts = (1..=2)
+                .map(|part| PartRun { part, status: status(part), time: Timing::default() })
+                .collect();
+            (Timing::default(), parts)
+        },
+    };
+
+    DayRun { day, title: solver.title(), input, parse_time, parts }
+}
+
+fn read_file(path: &Path) -> Result<String> {
+    let io_error = |source| Error::Io { path: path.display().to_string(), source };
+    let file = File::open(path).map_err(io_error)?;
+    solver::read_input(BufReader::new(file)).map_err(io_error)
+}
+
+/// The results of a run over several days, printable as a table.
+pub struct Summary {
+    pub days: Vec<DayRun>,
+}
+
+impl Summary {
+    pub fn has_failures(&self) -> bool {
+        self.days.iter()
+            .flat_map(|d| &d.parts)
+            .any(|p| matches!(p.status, Status::Failed(_)))
+    }
+
+    /// Total time of the run, counting the mean time of each step.
+    pub fn total_time(&self) -> Duration {
+        self.days.iter()
+            .map(|d| d.parse_time.mean + d.parts.iter().map(|p| p.time.mean).sum::<Duration>())
+            .sum()
+    }
+
+    fn repeated(&self) -> bool {
+        self.days.iter().any(|d| d.parse_time.runs > 1)
+    }
+
+    /// Render the run in `format`.
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Text => format!("{}\n", self),
+            Format::Csv => self.to_csv(),
+            Format::Json => self.to_json(),
+        }
+    }
+
+    /// The run as CSV, one row per part, with times in nanoseconds. Only the
+    /// part 1 row of each day carries the parse time.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("day,part,input,status,answer,message,runs,parse_ns,min_ns,median_ns,mean_ns,max_ns\n");
+        for day in &self.days {
+            for p in &day.parts {
+                let (answer, message) = match &p.status {
+                    Status::Solved(answer) => (answer.as_str(), ""),
+                    Status::Skipped(reason) | Status::Failed(reason) => ("", reason.as_str()),
+                };
+                let parse = if p.part == 1 && day.parse_time.runs > 0 { day.parse_time.median.as_nanos().to_string() } else { String::new() };
+                let t = &p.time;
+                csv.push_str(&format!("{},{},{},{},{},{},{},{},{},{},{},{}\n",
+                    day.day, p.part, csv_field(&day.input.display().to_string()), p.status.name(),
+                    csv_field(answer), csv_field(message), t.runs, parse,
+                    t.min.as_nanos(), t.median.as_nanos(), t.mean.as_nanos(), t.max.as_nanos()));
+            }
+        }
+        csv
+    }
+
+    /// The run as a JSON document, with times in nanoseconds:
+    ///
+    /// ```json
+    /// { "days": [ { "day": 6, "title": "Wait For It", "input": "inputs/day06.txt",
+    ///               "parse": { "runs": 1, "min_ns": 3100, ... },
+    ///               "parts": [ { "part": 1, "status": "solved", "answer": "288",
+    ///                            "message": null, "time": { ... } }, ... ] } ],
+    ///   "total_ns": 12345 }
+    /// ```
+    pub fn to_json(&self) -> String {
+        let days: Vec<Value> = self.days.iter().map(|day| {
+            let parts: Vec<Value> = day.parts.iter().map(|p| {
+                let (answer, message) = match &p.status {
+                    Status::Solved(answer) => (Some(answer), None),
+                    Status::Skipped(reason) | Status::Failed(reason) => (None, Some(reason)),
+                };
+                json!({
+                    "part": p.part,
+                    "status": p.status.name(),
+                    "answer": answer,
+                    "message": message,
+                    "time": timing_json(&p.time),
+                })
+            }).collect();
+
+            json!({
+                "day": day.day,
+                "title": day.title,
+                "input": day.input.display().to_string(),
+                "parse": timing_json(&day.parse_time),
+                "parts": parts,
+            })
+        }).collect();
+
+        let summary = json!({ "days": days, "total_ns": self.total_time().as_nanos() as u64 });
+        format!("{:#}\n", summary)
+    }
+}
+
+fn timing_json(t: &Timing) -> Value {
+    json!({
+        "runs": t.runs,
+        "min_ns": t.min.as_nanos() as u64,
+        "median_ns": t.median.as_nanos() as u64,
+        "mean_ns": t.mean.as_nanos() as u64,
+        "max_ns": t.max.as_nanos() as u64,
+    })
+}
+
+pub(crate) fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let answer = |p: &PartRun| match &p.status {
+            Status::Solved(answer) => answer.clone(),
+            Status::Skipped(reason) => format!("skipped ({})", reason),
+            Status::Failed(reason) => format!("FAILED: {}", reason),
+        };
+
+        // With repeated runs, show the spread of the solve times rather than
+        // a single one.
+        let repeated = self.repeated();
+        let headers: &[&str] = if repeated { &["Parse", "Min", "Median", "Mean", "Max"] } else { &["Parse", "Solve"] };
+        let times = |day: &DayRun, p: &PartRun| {
+            // a day whose input could not be read was never parsed
+            let parse = if p.part == 1 && day.parse_time.runs > 0 { format_duration(day.parse_time.median) } else { String::new() };
+            let t = &p.time;
+            let mut times = vec!(parse);
+            if t.runs == 0 {
+                times.resize(headers.len(), String::new());
+            } else if repeated {
+                times.extend([t.min, t.median, t.mean, t.max].map(format_duration));
+            } else {
+                times.push(format_duration(t.mean));
+            }
+            times
+        };
+
+        let width = self.days.iter()
+            .flat_map(|d| &d.parts)
+            .map(|p| answer(p).chars().count())
+            .chain(["Answer".len()])
+            .max()
+            .unwrap_or(0);
+
+        let row = |day: &dyn fmt::Display, part: &dyn fmt::Display, answer: &str, times: &[String]| {
+            let times: String = times.iter().map(|t| format!("  {:>12}", t)).collect();
+            format!("{:>3}  {:>4}  {:<w$}{}", day, part, answer, times, w = width)
+        };
+
+        let headers: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+        writeln!(f, "{}", row(&"Day", &"Part", "Answer", &headers))?;
+        for day in &self.days {
+            for p in &day.parts {
+                writeln!(f, "{}", row(&day.day, &p.part, &answer(p), &times(day, p)).trim_end())?;
+            }
+        }
+        write!(f, "{:<w$}  {:>12}", "Total", format_duration(self.total_time()), w = 3 + 2 + 4 + 2 + width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_days() {
+        let days: Vec<u32> = select_days("3-5").unwrap().iter().map(|s| s.day()).collect();
+        assert_eq!(vec!(3, 4, 5), days);
+
+        let days: Vec<u32> = select_days("7").unwrap().iter().map(|s| s.day()).collect();
+        assert_eq!(vec!(7), days);
+
+        assert_eq!(SOLVERS.len(), select_days("all").unwrap().len());
+        assert!(select_days("20-30").is_err());
+        assert!(select_days("one").is_err());
+
+        let err = select_days("8-6").err().unwrap();
+        assert_eq!("the range \"8-6\" ends before it starts; did you mean 6-8?", err.to_string());
+    }
+
+    #[test]
+    fn test_timing() {
+        let ms = Duration::from_millis;
+        let timing = Timing::from_samples(vec!(ms(4), ms(1), ms(3), ms(8)));
+        assert_eq!(Timing { runs: 4, min: ms(1), median: ms(3) + Duration::from_micros(500), mean: ms(4), max: ms(8) }, timing);
+
+        assert_eq!(Timing::default(), Timing::from_samples(vec!()));
+
+        let (answer, timing) = measure(3, || 42);
+        assert_eq!(42, answer);
+        assert_eq!(3, timing.runs);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("999 ns", format_duration(Duration::from_nanos(999)));
+        assert_eq!("1.500 µs", format_duration(Duration::from_nanos(1_500)));
+        assert_eq!("12.345 ms", format_duration(Duration::from_micros(12_345)));
+        assert_eq!("2.000 s", format_duration(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_input_path() {
+        assert_eq!(Path::new("inputs/day03.txt"), input_path(Path::new("inputs"), 3));
+    }
+
+    #[test]
+    fn test_solve() {
+        let solver = solver::find(6).unwrap();
+        let (parse_time, parts) = solve(solver, "Time:      7  15   30\nDistance:  9  40  200\n", 5);
+        assert_eq!(5, parse_time.runs);
+        assert_eq!(5, parts[0].time.runs);
+        assert_eq!(Status::Solved("288".to_string()), parts[0].status);
+        assert_eq!(Status::Solved("71503".to_string()), parts[1].status);
+
+        let solver = solver::find(14).unwrap();
+        let (_, parts) = solve(solver, "O.#\n.O.\n", 1);
+        assert_eq!(Status::Skipped("not solved yet".to_string()), parts[1].status);
+
+        let (_, parts) = solve(solver, "O.#\n.X.\n", 1);
+        assert!(matches!(parts[0].status, Status::Failed(_)));
+    }
+
+    #[test]
+    fn test_run_day_missing_input() {
+        let solver = solver::find(1).unwrap();
+        let run = run_day(solver, Path::new("no/such/dir"), 1);
+        assert_eq!(2, run.parts.len());
+        for part in run.parts {
+            assert!(matches!(part.status, Status::Skipped(_)));
+        }
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let solver = solver::find(14).unwrap();
+        let (parse_time, parts) = solve(solver, "O.#\n.O.\n", 1);
+        let summary = Summary { days: vec!(DayRun { day: 14, title: "", input: PathBuf::new(), parse_time, parts }) };
+
+        let csv = summary.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!("day,part,input,status,answer,message,runs,parse_ns,min_ns,median_ns,mean_ns,max_ns", rows[0]);
+        assert!(rows[1].starts_with("14,1,,solved,4,,1,"), "{}", rows[1]);
+        assert_eq!("14,2,,skipped,,not solved yet,0,,0,0,0,0", rows[2]);
+
+        assert_eq!("\"line 1, column 2: oops\"", csv_field("line 1, column 2: oops"));
+        assert_eq!("\"say \"\"hi\"\"\"", csv_field("say \"hi\""));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let solver = solver::find(14).unwrap();
+        let (parse_time, parts) = solve(solver, "O.#\n.O.\n", 1);
+        let input = PathBuf::from("inputs/day14.txt");
+        let summary = Summary { days: vec!(DayRun { day: 14, title: "Parabolic Reflector Dish", input, parse_time, parts }) };
+
+        let json: Value = serde_json::from_str(&summary.to_json()).unwrap();
+        let day = &json["days"][0];
+        assert_eq!(14, day["day"]);
+        assert_eq!("inputs/day14.txt", day["input"]);
+        assert_eq!(1, day["parse"]["runs"]);
+        assert_eq!(json!("solved"), day["parts"][0]["status"]);
+        assert_eq!(json!("4"), day["parts"][0]["answer"]);
+        assert_eq!(Value::Null, day["parts"][0]["message"]);
+        assert_eq!(json!("skipped"), day["parts"][1]["status"]);
+        assert_eq!(json!("not solved yet"), day["parts"][1]["message"]);
+        assert_eq!(0, day["parts"][1]["time"]["runs"]);
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!(Format::Json, "json".parse().unwrap());
+        assert!("yaml".parse::<Format>().is_err());
+    }
+}
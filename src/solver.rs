@@ -0,0 +1,208 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::io::{ self, BufRead };
+
+use crate::*;
+use crate::error::Result;
+use crate::runner::Format;
+
+/// A solution to a single day's puzzle.
+///
+/// Solving happens in two phases: `parse` turns the raw puzzle text into the
+/// day's model (an `Almanac`, a `Grid`, ...) once, and both parts then work
+/// from that same model.
+///
+/// Every `dayNN` module exposes a unit struct implementing this trait, and
+/// `SOLVERS` collects them so that the runner (and anything else that needs
+/// to walk over the days) can do so without naming each module.
+pub trait Solver: Sync {
+    /// The parsed puzzle input shared by both parts.
+    type Input: 'static;
+
+    /// Day of the advent calendar, starting from 1.
+    fn day(&self) -> u32;
+
+    /// Puzzle title as shown on the Advent of Code website.
+    fn title(&self) -> &'static str;
+
+    /// Parse the puzzle input. Use `read_input` to get the text out of a
+    /// file, stdin or any other buffered reader. Malformed input is reported
+    /// as an `Error::Parse` pointing at the offending line and column.
+    fn parse(&self, input: &str) -> Result<Self::Input>;
+
+    fn part1(&self, input: &Self::Input) -> Result<String>;
+
+    fn part2(&self, input: &Self::Input) -> Result<String>;
+
+    /// An account of how the answers come about in `format`, for checking
+    /// surprising results. Most days have nothing to add, and some can only
+    /// explain themselves as text.
+    fn explain(&self, _input: &Self::Input, _format: Format) -> Option<String> {
+        None
+    }
+
+    /// A copy of this solver set up as `settings` say, or `None` if none of
+    /// them apply to this day. Invalid values are reported as `Error::Usage`.
+    fn configure(&self, _settings: &Settings) -> Result<Option<Self>> where Self: Sized {
+        Ok(None)
+    }
+}
+
+/// Puzzle settings by name, such as the cubes in day 2's bag, for solvers
+/// to pick up in `Solver::configure`. Each day ignores the settings that are
+/// meant for other days.
+#[derive(Debug, Default)]
+pub struct Settings {
+    values: BTreeMap<String, String>,
+}
+
+impl Settings {
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.values.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+}
+
+/// A parsed puzzle input whose concrete type has been erased.
+pub type Parsed = Box<dyn Any>;
+
+/// Object-safe version of `Solver`, so that solvers for different days
+/// (each with its own `Input` type) can live side by side in `SOLVERS`.
+///
+/// This is implemented for every `Solver`; there is no need to implement it
+/// by hand.
+pub trait DynSolver: Sync {
+    fn day(&self) -> u32;
+
+    fn title(&self) -> &'static str;
+
+    fn parse(&self, input: &str) -> Result<Parsed>;
+
+    /// Solve part 1. Panics if `parsed` did not come from this solver.
+    fn part1(&self, parsed: &Parsed) -> Result<String>;
+
+    /// Solve part 2. Panics if `parsed` did not come from this solver.
+    fn part2(&self, parsed: &Parsed) -> Result<String>;
+
+    /// See `Solver::explain`. Panics if `parsed` did not come from this solver.
+    fn explain(&self, parsed: &Parsed, format: Format) -> Option<String>;
+
+    /// See `Solver::configure`.
+    fn configure(&self, settings: &Settings) -> Result<Option<Box<dyn DynSolver>>>;
+}
+
+impl<S: Solver + 'static> DynSolver for S {
+    fn day(&self) -> u32 {
+        Solver::day(self)
+    }
+
+    fn title(&self) -> &'static str {
+        Solver::title(self)
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed> {
+        Ok(Box::new(Solver::parse(self, input)?))
+    }
+
+    fn part1(&self, parsed: &Parsed) -> Result<String> {
+        Solver::part1(self, downcast::<S>(parsed))
+    }
+
+    fn part2(&self, parsed: &Parsed) -> Result<String> {
+        Solver::part2(self, downcast::<S>(parsed))
+    }
+
+    fn explain(&self, parsed: &Parsed, format: Format) -> Option<String> {
+        Solver::explain(self, downcast::<S>(parsed), format)
+    }
+
+    fn configure(&self, settings: &Settings) -> Result<Option<Box<dyn DynSolver>>> {
+        Ok(Solver::configure(self, settings)?.map(|solver| Box::new(solver) as Box<dyn DynSolver>))
+    }
+}
+
+fn downcast<S: Solver>(parsed: &Parsed) -> &S::Input {
+    parsed.downcast_ref::<S::Input>()
+        .expect("Parsed input was produced by a different solver")
+}
+
+/// All available solvers, ordered by day.
+pub static SOLVERS: &[&dyn DynSolver] = &[
+    &day01::Day01,
+    &day02::Day02::new(),
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+    &day08::Day08,
+    &day09::Day09,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+    &day18::Day18,
+    &day19::Day19,
+    &day20::Day20,
+    &day21::Day21,
+    &day22::Day22,
+    &day23::Day23,
+    &day24::Day24,
+    &day25::Day25,
+];
+
+/// Read a complete puzzle input from any buffered source, e.g. a file,
+/// stdin or a network stream.
+pub fn read_input<R: BufRead>(mut reader: R) -> io::Result<String> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    Ok(input)
+}
+
+/// Look up the solver for a given day.
+pub fn find(day: u32) -> Option<&'static dyn DynSolver> {
+    SOLVERS.iter().find(|s| s.day() == day).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solvers_are_ordered_by_day() {
+        for (i, solver) in SOLVERS.iter().enumerate() {
+            assert_eq!(i as u32 + 1, solver.day(), "{}", solver.title());
+        }
+    }
+
+    #[test]
+    fn test_read_input() {
+        let reader = io::Cursor::new("1abc2\ntreb7uchet\n");
+        let input = read_input(reader).unwrap();
+        assert_eq!("1abc2\ntreb7uchet\n", input);
+        let solver = find(1).unwrap();
+        let parsed = solver.parse(&input).unwrap();
+        assert_eq!("89", solver.part1(&parsed).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "different solver")]
+    fn test_parsed_input_from_another_day() {
+        let parsed = find(1).unwrap().parse("1abc2").unwrap();
+        let _ = find(2).unwrap().part1(&parsed);
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(7, find(7).unwrap().day());
+        assert!(find(0).is_none());
+        assert!(find(26).is_none());
+    }
+}
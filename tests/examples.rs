@@ -0,0 +1,55 @@
+//! Runs every puzzle example in `tests/examples` through the solver registry.
+//!
+//! Each example is a pair of files: `NAME.txt` holds the puzzle input and
+//! `NAME.toml` the expected answers, in the same format as `answers.toml`
+//! (see `answers::Answers`). A part without an expected answer is not run,
+//! so an example that only applies to one part lists just that part.
+
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use advent_of_code_2023::answers::Answers;
+use advent_of_code_2023::solver;
+
+fn examples() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/examples");
+    let mut examples: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap_or_else(|err| panic!("{}: {}", dir.display(), err))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    examples.sort();
+    examples
+}
+
+/// Check one example, returning a description of each answer that was wrong.
+fn check(expected: &Path) -> Vec<String> {
+    let name = expected.file_stem().unwrap().to_string_lossy();
+    let answers = Answers::load(expected).unwrap_or_else(|err| panic!("{}: {}", expected.display(), err));
+    let input_path = expected.with_extension("txt");
+    let input = fs::read_to_string(&input_path).unwrap_or_else(|err| panic!("{}: {}", input_path.display(), err));
+
+    let mut failures = vec!();
+    for (day, part, answer) in answers.iter() {
+        let solver = solver::find(day).unwrap_or_else(|| panic!("{}: there is no solver for day {}", name, day));
+        let result = solver.parse(&input).and_then(|parsed| {
+            if part == 1 { solver.part1(&parsed) } else { solver.part2(&parsed) }
+        });
+
+        match result {
+            Ok(actual) if actual == answer => {},
+            Ok(actual) => failures.push(format!("{} part {}: expected {}, got {}", name, part, answer, actual)),
+            Err(err) => failures.push(format!("{} part {}: {}", name, part, err)),
+        }
+    }
+    failures
+}
+
+#[test]
+fn test_examples() {
+    let examples = examples();
+    assert!(!examples.is_empty(), "no examples found");
+
+    let failures: Vec<String> = examples.iter().flat_map(|example| check(example)).collect();
+    assert!(failures.is_empty(), "{} example answers were wrong:\n{}", failures.len(), failures.join("\n"));
+}